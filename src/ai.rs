@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    ball::{self, Arena, Ball},
+    bat::{Bat, BatInput, Controller},
+    settings::{Difficulty, GameRng, Settings},
+    state::{GameState, State},
+    tuning::GameTuning,
+};
+
+const DEADZONE: f32 = 0.25;
+const RUN_DISTANCE: f32 = 4.0;
+
+pub struct Cpu {
+    pub difficulty: Difficulty,
+    aim: Option<f32>,
+}
+
+impl Cpu {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            aim: None,
+        }
+    }
+}
+
+impl Difficulty {
+    /// How far from the center of the bat the cpu may try to hit the ball.
+    /// Anything past the hit radius is a miss.
+    fn aim_error(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 5.5,
+            Difficulty::Normal => 4.5,
+            Difficulty::Hard => 4.1,
+        }
+    }
+    /// How early the cpu swings, as a share of the hit radius.
+    /// Swinging early returns the ball faster.
+    fn swing_reach(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.65,
+            Difficulty::Normal => 0.8,
+            Difficulty::Hard => 0.9,
+        }
+    }
+    fn can_run(&self) -> bool {
+        !matches!(self, Difficulty::Easy)
+    }
+}
+
+pub fn update(
+    tuning: Res<GameTuning>,
+    settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
+    state: Query<&State>,
+    arena: Res<Arena>,
+    ball: Query<&Ball>,
+    mut bats: Query<(&Bat, &mut Controller, &mut BatInput)>,
+) {
+    let state = state.single();
+    let ball = ball.single();
+    for (bat, mut controller, mut input) in &mut bats {
+        let Controller::Cpu(cpu) = controller.as_mut() else {
+            continue;
+        };
+        *input = BatInput::default();
+        if !matches!(state.game_state, GameState::Playing) {
+            cpu.aim = None;
+            continue;
+        }
+
+        let target_x = if ball.last_hit == bat.variant {
+            cpu.aim = None;
+            0.0
+        } else {
+            let error = cpu.difficulty.aim_error();
+            let aim = *cpu
+                .aim
+                .get_or_insert_with(|| rng.0.gen_range(-error..=error));
+            let landing = ball::trajectory(ball, arena.edge, ball::playable_range(settings.mode));
+            let landing = landing.last().copied().unwrap_or(ball.position);
+
+            let distance_y = bat.variant.default_y_position() - ball.position.y;
+            let approaching = distance_y * ball.velocity.y > 0.0;
+            input.swing =
+                approaching && distance_y.abs() <= tuning.hit_radius * cpu.difficulty.swing_reach();
+
+            landing.x + aim
+        };
+
+        let diff = target_x - bat.position_x;
+        input.left = diff < -DEADZONE;
+        input.right = diff > DEADZONE;
+        input.run = cpu.difficulty.can_run() && diff.abs() > RUN_DISTANCE;
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    bat::Variant,
+    events::{FastestShot, PointScored, PointStreak},
+    players::PlayerSettings,
+    settings::{GameMode, MatchFormat, Settings},
+    state::{self, GameState, State},
+    text::{self, BitmapSection, BitmapText},
+    theme::Themes,
+};
+
+/// How long each banner stays up, in seconds.
+const BANNER_SECONDS: f32 = 1.5;
+/// Streaks are announced at three points and then every five.
+const FIRST_STREAK: u32 = 3;
+const STREAK_STEP: u32 = 5;
+
+/// A message, followed by the name of the player it's about.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Banner {
+    pub message: String,
+    pub player: Option<Variant>,
+}
+
+impl Banner {
+    pub fn new(message: impl Into<String>, player: Option<Variant>) -> Self {
+        Self {
+            message: message.into(),
+            player,
+        }
+    }
+}
+
+/// Banners waiting their turn, shown one at a time so they never overlap.
+#[derive(Resource, Default)]
+pub struct Announcements {
+    pub queue: VecDeque<Banner>,
+    pub showing: Option<(Banner, Timer)>,
+}
+
+impl Announcements {
+    /// Queues `banner` unless the same one is already up or waiting.
+    pub fn push(&mut self, banner: Banner) {
+        let showing = self.showing.as_ref().map(|(showing, _)| showing);
+        if showing != Some(&banner) && !self.queue.contains(&banner) {
+            self.queue.push_back(banner);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.showing = None;
+    }
+}
+
+/// What's at stake after a point: a game won, deuce, or a game or match point.
+pub fn situation(state: &State, format: &MatchFormat) -> Option<Banner> {
+    if state.match_over {
+        return None;
+    }
+    let points = state.points;
+    if points.light == 0 && points.dark == 0 {
+        let winner = state.game_scores.last().map(|score| {
+            if score.light > score.dark {
+                Variant::Light
+            } else {
+                Variant::Dark
+            }
+        })?;
+        return Some(Banner::new("GAME", Some(winner)));
+    }
+    let deuce_from = format.points_per_game.saturating_sub(1).max(1);
+    if format.win_by > 1 && points.light == points.dark && points.light >= deuce_from {
+        return Some(Banner::new("DEUCE", None));
+    }
+    for variant in [Variant::Light, Variant::Dark] {
+        if state.match_point(&variant, format) {
+            return Some(Banner::new("MATCH POINT", Some(variant)));
+        }
+    }
+    for variant in [Variant::Light, Variant::Dark] {
+        let opponent = points.get(&variant.opponent());
+        if format.is_game_won(points.get(&variant) + 1, opponent) {
+            return Some(Banner::new("GAME POINT", Some(variant)));
+        }
+    }
+    None
+}
+
+fn streak_announced(points: u32) -> bool {
+    points == FIRST_STREAK || (points >= STREAK_STEP && points.is_multiple_of(STREAK_STEP))
+}
+
+/// Queues banners for the points and records of this tick, right after the score and stats.
+pub fn queue(
+    settings: Res<Settings>,
+    mut announcements: ResMut<Announcements>,
+    mut points: EventReader<PointScored>,
+    mut streaks: EventReader<PointStreak>,
+    mut fastest: EventReader<FastestShot>,
+    state: Query<&State>,
+) {
+    let state = state.single();
+    let practice = settings.mode == GameMode::Practice;
+    if !points.is_empty() {
+        points.clear();
+        if let Some(banner) = situation(state, &settings.format).filter(|_| !practice) {
+            announcements.push(banner);
+        }
+    }
+    for streak in streaks.iter() {
+        if !practice && streak_announced(streak.points) {
+            announcements.push(Banner::new(
+                format!("{} POINT STREAK", streak.points),
+                Some(streak.by.clone()),
+            ));
+        }
+    }
+    for shot in fastest.iter() {
+        announcements.push(Banner::new("FASTEST SHOT", Some(shot.by.clone())));
+    }
+}
+
+#[derive(Component)]
+pub struct BannerUI;
+
+pub fn spawn(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle {
+            transform: Transform::from_scale(Vec3::splat(2.0 * text::PIXEL))
+                .with_translation(Vec3::new(0.0, 0.0, 250.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        BitmapText::default().with_alignment(TextAlignment::Center),
+        BannerUI,
+    ));
+}
+
+/// Shows the banner in front for a while, bobbing like the new game text, then the next one.
+#[allow(clippy::too_many_arguments)]
+pub fn show(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    players: Res<PlayerSettings>,
+    themes: Res<Themes>,
+    mut announcements: ResMut<Announcements>,
+    state: Query<&State>,
+    window: Query<&Window>,
+    mut banner_ui: Query<(&mut BitmapText, &mut Transform, &mut Visibility), With<BannerUI>>,
+) {
+    if matches!(state.single().game_state, GameState::NewGame) {
+        announcements.clear();
+    }
+    if let Some((_, timer)) = &mut announcements.showing {
+        if timer.tick(time.delta()).finished() {
+            announcements.showing = None;
+        }
+    }
+    if announcements.showing.is_none() {
+        announcements.showing = announcements
+            .queue
+            .pop_front()
+            .map(|banner| (banner, Timer::from_seconds(BANNER_SECONDS, TimerMode::Once)));
+    }
+
+    let (mut text, mut transform, mut visibility) = banner_ui.single_mut();
+    let Some((banner, _)) = &announcements.showing else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    visibility.set_if_neq(Visibility::Inherited);
+    let theme = themes.current(&settings);
+    let mut sections = vec![BitmapSection {
+        value: banner.message.clone(),
+        color: theme.ink,
+    }];
+    if let Some(player) = &banner.player {
+        sections.push(BitmapSection {
+            value: format!(" {}", players.name(&settings, player)),
+            color: theme.readable(players.color(player)),
+        });
+    }
+    text.set_if_neq(BitmapText {
+        sections,
+        ..text.clone()
+    });
+    transform.translation.y = window.single().height() * 0.25 + state::bob(&time);
+}
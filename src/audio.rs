@@ -1,60 +1,418 @@
-use bevy::{audio::PlaybackMode, prelude::*};
+use bevy::{
+    audio::{PlaybackMode, SpatialSettings, Volume},
+    prelude::*,
+};
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
 
+use crate::{
+    audio_settings::AudioSettings,
+    ball::{Arena, Ball},
+    bat::Variant,
+    events::{
+        BallHit, CountdownTick, MenuConfirmed, MenuMoved, Paused, PointScored, Resumed, Served,
+        WallBounce,
+    },
+    settings::Settings,
+    state::{GameState, State},
+    theme::{ThemeMusic, Themes},
+    tuning::GameTuning,
+};
+
+/// Crossfades start on a beat of the stems and take a bar.
+const BEATS_PER_BAR: f32 = 4.0;
+/// The ears sit at -1 and 1 on x with effects played in front of them, between the two.
+const EAR_GAP: f32 = 2.0;
+/// Spatial sounds are quieter the further they are from each ear, this brings a centered effect
+/// back to full volume.
+const SPATIAL_GAIN: f32 = 2.5;
+const SOFT_HIT_VOLUME: f32 = 0.6;
+/// Playback speed of the hardest hits, pitching them up.
+const HARD_HIT_SPEED: f32 = 1.15;
+/// Every effect is played up to this much faster or slower, so repeats don't sound the same.
+const PITCH_VARIATION: f32 = 0.05;
+
+/// Something the game makes a sound for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    Hit,
+    Bounce,
+    Serve,
+    Miss,
+    Pause,
+    Resume,
+    MenuMove,
+    MenuConfirm,
+    Countdown,
+}
+
+/// The samples an effect picks one of at random and how loud they're played.
+pub struct Sample {
+    pub variants: &'static [&'static str],
+    pub volume: f32,
+}
+
+impl Effect {
+    pub fn sample(&self) -> Sample {
+        let (variants, volume): (&'static [&'static str], f32) = match self {
+            Effect::Hit => (&["hit.ogg", "hit-2.wav", "hit-3.wav"], 1.0),
+            Effect::Bounce => (&["bounce.wav"], 0.6),
+            Effect::Serve => (&["serve.wav"], 0.6),
+            Effect::Miss => (&["miss.wav"], 0.7),
+            Effect::Pause => (&["pause.wav"], 0.5),
+            Effect::Resume => (&["resume.wav"], 0.5),
+            Effect::MenuMove => (&["menu-move.wav"], 0.4),
+            Effect::MenuConfirm => (&["menu-confirm.wav"], 0.5),
+            Effect::Countdown => (&["countdown.wav"], 0.5),
+        };
+        Sample { variants, volume }
+    }
+}
+
+/// How an effect sounds, panned towards where it happened.
+#[derive(Debug, PartialEq)]
+pub struct EffectSound {
+    pub effect: Effect,
+    pub pan: f32,
+    pub volume: f32,
+    pub speed: f32,
+}
+
+impl EffectSound {
+    /// Centered and played as loud as the effect's sample says, at its own pitch.
+    pub fn new(effect: Effect) -> Self {
+        Self {
+            effect,
+            pan: 0.0,
+            volume: effect.sample().volume,
+            speed: 1.0,
+        }
+    }
+
+    pub fn at(self, x: f32, edge: f32) -> Self {
+        Self {
+            pan: (x / edge).clamp(-1.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Louder and brighter the harder the ball was hit.
+    pub fn hit(hit: &BallHit, edge: f32, max_speed: f32) -> Self {
+        let hardness = (hit.velocity.length() / max_speed).clamp(0.0, 1.0);
+        let sound = Self::new(Effect::Hit).at(hit.position.x, edge);
+        Self {
+            volume: sound.volume * (SOFT_HIT_VOLUME + (1.0 - SOFT_HIT_VOLUME) * hardness),
+            speed: sound.speed * (1.0 + (HARD_HIT_SPEED - 1.0) * hardness),
+            ..sound
+        }
+    }
+}
+
+/// Picks effect variants and pitches, kept apart from [`crate::settings::GameRng`] so sounds
+/// don't change how the cpu plays.
+#[derive(Resource)]
+pub struct EffectRng(SmallRng);
+
+impl Default for EffectRng {
+    fn default() -> Self {
+        Self(SmallRng::from_entropy())
+    }
+}
+
+/// An effect still playing, with its volume before the effects volume.
 #[derive(Component)]
-pub struct Hit;
+pub struct PlayingEffect(f32);
 
 #[derive(Component)]
 pub enum Music {
+    Menu,
     Zero,
     One,
     Two,
 }
 
-pub fn spawn_hit_sound(commands: &mut Commands, asset_server: &Res<AssetServer>) {
+impl Music {
+    fn index(&self) -> usize {
+        match self {
+            Music::Menu => 0,
+            Music::Zero => 1,
+            Music::One => 2,
+            Music::Two => 3,
+        }
+    }
+}
+
+/// Volumes of the menu track and the three stems, in the order of [`Music::index`].
+#[derive(Resource, Debug)]
+pub struct MusicMix {
+    pub target: [f32; 4],
+    pub volumes: [f32; 4],
+    /// When the stems started playing together, in real seconds, `None` while they load.
+    pub started: Option<f64>,
+    /// Tempo of the stems playing.
+    pub bpm: f32,
+    beat: u64,
+}
+
+impl Default for MusicMix {
+    fn default() -> Self {
+        Self {
+            target: [0.0; 4],
+            volumes: [0.0; 4],
+            started: None,
+            bpm: STEMS_BPM,
+            beat: 0,
+        }
+    }
+}
+
+impl MusicMix {
+    /// The beat of the stems at `now`, counted from when they started.
+    pub fn beat(&self, now: f64) -> u64 {
+        let since = self.started.map_or(now, |started| now - started);
+        (since * self.bpm as f64 / 60.0) as u64
+    }
+}
+
+/// How intense the match is right now, from 0 for a calm serve up to 2 for a hot rally on match
+/// point.
+pub fn intensity(state: &State, ball: &Ball, tuning: &GameTuning, settings: &Settings) -> f32 {
+    let speed = (ball.velocity.length() / ball.max_speed(tuning)).min(1.0);
+    let mut intensity = 0.8 * ball.heat + 0.4 * speed;
+    // practice has no opponent to be close to
+    if settings.has_player(&Variant::Dark) {
+        if state.points.light.abs_diff(state.points.dark) <= 1
+            && state.points.light + state.points.dark >= 2
+        {
+            intensity += 0.3;
+        }
+        if [Variant::Light, Variant::Dark]
+            .iter()
+            .any(|variant| state.match_point(variant, &settings.format))
+        {
+            intensity += 0.5;
+        }
+    }
+    intensity.clamp(0.0, 2.0)
+}
+
+/// Crossfades from stem zero to one as the intensity goes up to 1 and on to two up to 2.
+pub fn stem_volumes(intensity: f32) -> [f32; 3] {
+    let two = (intensity - 1.0).clamp(0.0, 1.0);
+    let one = intensity.clamp(0.0, 1.0) - two;
+    [1.0 - one - two, one, two]
+}
+
+/// Plays an effect for everything that happens in the game and the menus.
+#[allow(clippy::too_many_arguments)]
+pub fn play_effects(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    audio: Res<AudioSettings>,
+    tuning: Res<GameTuning>,
+    arena: Res<Arena>,
+    mut rng: ResMut<EffectRng>,
+    mut hits: EventReader<BallHit>,
+    mut bounces: EventReader<WallBounce>,
+    mut serves: EventReader<Served>,
+    mut points: EventReader<PointScored>,
+    mut paused: EventReader<Paused>,
+    mut resumed: EventReader<Resumed>,
+    mut moved: EventReader<MenuMoved>,
+    mut confirmed: EventReader<MenuConfirmed>,
+    mut countdown: EventReader<CountdownTick>,
+) {
+    let mut sounds: Vec<EffectSound> = hits
+        .iter()
+        .map(|hit| EffectSound::hit(hit, arena.edge, tuning.max_ball_speed))
+        .collect();
+    sounds.extend(
+        bounces
+            .iter()
+            .map(|bounce| EffectSound::new(Effect::Bounce).at(bounce.position.x, arena.edge)),
+    );
+    sounds.extend(serves.iter().map(|_| EffectSound::new(Effect::Serve)));
+    sounds.extend(points.iter().map(|_| EffectSound::new(Effect::Miss)));
+    sounds.extend(paused.iter().map(|_| EffectSound::new(Effect::Pause)));
+    sounds.extend(resumed.iter().map(|_| EffectSound::new(Effect::Resume)));
+    sounds.extend(moved.iter().map(|_| EffectSound::new(Effect::MenuMove)));
+    sounds.extend(
+        confirmed
+            .iter()
+            .map(|_| EffectSound::new(Effect::MenuConfirm)),
+    );
+    sounds.extend(
+        countdown
+            .iter()
+            .map(|_| EffectSound::new(Effect::Countdown)),
+    );
+
+    if audio.effects_volume() <= 0.0 {
+        return;
+    }
+    for sound in &sounds {
+        spawn_effect(
+            &mut commands,
+            &asset_server,
+            &mut rng.0,
+            sound,
+            audio.effects_volume(),
+        );
+    }
+}
+
+pub fn spawn_effect(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    rng: &mut impl Rng,
+    sound: &EffectSound,
+    effects_volume: f32,
+) {
+    let variant = sound
+        .effect
+        .sample()
+        .variants
+        .choose(rng)
+        .copied()
+        .unwrap_or("hit.ogg");
+    let speed = sound.speed * (1.0 + rng.gen_range(-PITCH_VARIATION..=PITCH_VARIATION));
     commands.spawn((
-        AudioBundle {
-            source: asset_server.load("hit.ogg"),
+        SpatialAudioBundle {
+            source: asset_server.load(variant),
             settings: PlaybackSettings {
                 mode: PlaybackMode::Despawn,
+                volume: Volume::new_relative(sound.volume * SPATIAL_GAIN * effects_volume),
+                speed,
                 ..default()
             },
+            spatial: SpatialSettings::new(
+                Transform::IDENTITY,
+                EAR_GAP,
+                Vec3::new(sound.pan, 0.0, 1.0),
+            ),
         },
-        Hit,
+        PlayingEffect(sound.volume * SPATIAL_GAIN),
     ));
 }
 
+/// Applies volume changes to the effects still playing.
+pub fn update_effects(
+    audio: Res<AudioSettings>,
+    effects: Query<(&SpatialAudioSink, &PlayingEffect)>,
+) {
+    if !audio.is_changed() {
+        return;
+    }
+    for (sink, effect) in &effects {
+        sink.set_volume(effect.0 * audio.effects_volume());
+    }
+}
+
+/// The match stems used by themes without their own.
+const STEMS: [&str; 3] = ["music-step-0.ogg", "music-step-1.ogg", "music-step-2.ogg"];
+const STEMS_BPM: f32 = 120.0;
+
 pub fn spawn_music(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn((
         AudioBundle {
-            source: asset_server.load("music-step-0.ogg"),
-            settings: PlaybackSettings {
-                mode: PlaybackMode::Loop,
-                paused: false,
-                ..default()
-            },
-        },
-        Music::Zero,
-    ));
-    commands.spawn((
-        AudioBundle {
-            source: asset_server.load("music-step-1.ogg"),
+            source: asset_server.load("music.ogg"),
             settings: PlaybackSettings {
                 mode: PlaybackMode::Loop,
                 paused: false,
                 ..default()
             },
         },
-        Music::One,
+        Music::Menu,
     ));
-    commands.spawn((
-        AudioBundle {
-            source: asset_server.load("music-step-2.ogg"),
-            settings: PlaybackSettings {
-                mode: PlaybackMode::Loop,
-                paused: false,
-                ..default()
+}
+
+/// Starts the three match stems of the picked theme, swapping them when it changes.
+pub fn spawn_stems(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    mut mix: ResMut<MusicMix>,
+    mut playing: Local<Option<Option<ThemeMusic>>>,
+    music: Query<(Entity, &Music)>,
+) {
+    let stems = &themes.current(&settings).music;
+    if playing.as_ref() == Some(stems) {
+        return;
+    }
+    *playing = Some(stems.clone());
+    mix.started = None;
+    mix.bpm = stems.as_ref().map_or(STEMS_BPM, |music| music.bpm);
+    for (entity, music) in &music {
+        if !matches!(music, Music::Menu) {
+            commands.entity(entity).despawn();
+        }
+    }
+    let paths = stems
+        .as_ref()
+        .map_or(STEMS.map(String::from), |music| music.stems.clone());
+    for (path, music) in paths.into_iter().zip([Music::Zero, Music::One, Music::Two]) {
+        // started by [`update_music`] once all three are loaded, so they stay in step
+        commands.spawn((
+            AudioBundle {
+                source: asset_server.load(path),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Loop,
+                    paused: true,
+                    ..default()
+                },
             },
-        },
-        Music::Two,
-    ));
+            music,
+        ));
+    }
+}
+
+/// Follows the intensity of the match while playing and the calm menu track otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn update_music(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    audio: Res<AudioSettings>,
+    tuning: Res<GameTuning>,
+    mut mix: ResMut<MusicMix>,
+    state: Query<&State>,
+    ball: Query<&Ball>,
+    music: Query<(&AudioSink, &Music)>,
+) {
+    let state = state.single();
+    let ball = ball.single();
+    // the music plays on while the game is paused or slowed down
+    let now = time.raw_elapsed_seconds_f64();
+    if mix.started.is_none() {
+        let stems: Vec<&AudioSink> = music
+            .iter()
+            .filter(|(_, music)| !matches!(music, Music::Menu))
+            .map(|(sink, _)| sink)
+            .collect();
+        if stems.len() == 3 {
+            stems.iter().for_each(|sink| sink.play());
+            mix.started = Some(now);
+        }
+    }
+    let beat_length = 60.0 / mix.bpm;
+    let beat = mix.beat(now);
+    if beat != mix.beat {
+        mix.beat = beat;
+        mix.target = match state.game_state {
+            GameState::Countdown { .. } | GameState::Playing => {
+                let [zero, one, two] = stem_volumes(intensity(state, ball, &tuning, &settings));
+                [0.0, zero, one, two]
+            }
+            GameState::Paused | GameState::NewGame | GameState::Winner(_) => [1.0, 0.0, 0.0, 0.0],
+        };
+    }
+    let fade = time.raw_delta_seconds() / (beat_length * BEATS_PER_BAR);
+    let MusicMix {
+        target, volumes, ..
+    } = &mut *mix;
+    for (volume, target) in volumes.iter_mut().zip(target.iter()) {
+        *volume += (*target - *volume).clamp(-fade, fade);
+    }
+    for (sink, music) in &music {
+        sink.set_volume(mix.volumes[music.index()] * audio.music_volume());
+    }
 }
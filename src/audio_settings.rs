@@ -0,0 +1,225 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{MenuConfirmed, MenuMoved},
+    highscores::InitialsEntry,
+    keymap,
+    settings::Settings,
+    state::{GameState, State},
+    storage,
+    text::{self, BitmapText},
+    theme::Themes,
+};
+
+const VOLUME_STEP: f32 = 0.1;
+const SLIDER_WIDTH: usize = 10;
+const ROWS: [&str; 3] = ["master", "music", "effects"];
+
+/// Volumes from 0 to 1, the music and effects are scaled by the master volume.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            effects: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn music_volume(&self) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        self.master * self.music
+    }
+
+    pub fn effects_volume(&self) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        self.master * self.effects
+    }
+
+    fn volume_mut(&mut self, row: usize) -> &mut f32 {
+        match row {
+            0 => &mut self.master,
+            1 => &mut self.music,
+            _ => &mut self.effects,
+        }
+    }
+
+    /// Moves the volume in `row` by `steps` of [`VOLUME_STEP`].
+    pub fn adjust(&mut self, row: usize, steps: f32) {
+        let volume = self.volume_mut(row);
+        *volume = ((*volume + steps * VOLUME_STEP) / VOLUME_STEP).round() * VOLUME_STEP;
+        *volume = volume.clamp(0.0, 1.0);
+    }
+}
+
+/// The volume menu while it's open, `row` is the selected slider.
+#[derive(Resource, Default)]
+pub struct AudioMenu {
+    pub row: usize,
+}
+
+#[derive(Component)]
+pub struct AudioMenuUI;
+
+fn save(settings: &Settings, audio: &AudioSettings) {
+    if let Some(path) = &settings.audio {
+        if let Err(error) = storage::save_ron(path, audio) {
+            error!("{error}");
+        }
+    }
+}
+
+/// `--mute` starts muted without changing the saved settings.
+pub fn load(settings: Res<Settings>, mut audio: ResMut<AudioSettings>) {
+    if let Some(path) = &settings.audio {
+        match storage::load_ron(path) {
+            Ok(loaded) => *audio = loaded,
+            Err(error) => warn!("{error}"),
+        }
+    }
+    audio.muted |= settings.muted;
+}
+
+pub fn spawn(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(text::PIXEL))
+                .with_translation(Vec3::new(0.0, 0.0, 300.0)),
+        ),
+        BitmapText::new("", text::INK),
+        AudioMenuUI,
+    ));
+}
+
+pub fn toggle_mute(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut audio: ResMut<AudioSettings>,
+) {
+    if keys.just_pressed(keymap::mute()) {
+        audio.muted = !audio.muted;
+        save(&settings, &audio);
+    }
+}
+
+/// Opens the volume menu from any screen but a running match.
+pub fn open_menu(
+    mut commands: Commands,
+    mut keys: ResMut<Input<KeyCode>>,
+    entry: Option<Res<InitialsEntry>>,
+    state: Query<&State>,
+    mut opened: EventWriter<MenuConfirmed>,
+) {
+    let state = state.single();
+    if entry.is_some()
+        || matches!(
+            state.game_state,
+            GameState::Countdown { .. } | GameState::Playing
+        )
+    {
+        return;
+    }
+    if keys.clear_just_pressed(keymap::audio_menu()) {
+        commands.init_resource::<AudioMenu>();
+        opened.send(MenuConfirmed);
+    }
+}
+
+/// Takes every key while open, so `P` or `R` don't reach the game behind it.
+pub fn menu(
+    mut commands: Commands,
+    mut keys: ResMut<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut audio: ResMut<AudioSettings>,
+    mut menu: ResMut<AudioMenu>,
+    mut moved: EventWriter<MenuMoved>,
+    mut confirmed: EventWriter<MenuConfirmed>,
+) {
+    let navigation = [
+        keymap::cursor_left(),
+        keymap::cursor_right(),
+        keymap::letter_up(),
+        keymap::letter_down(),
+        keymap::mute(),
+    ];
+    if keys.any_just_pressed(navigation) {
+        moved.send(MenuMoved);
+    }
+    if keys.just_pressed(keymap::cursor_left()) {
+        audio.adjust(menu.row, -1.0);
+    }
+    if keys.just_pressed(keymap::cursor_right()) {
+        audio.adjust(menu.row, 1.0);
+    }
+    if keys.just_pressed(keymap::letter_up()) {
+        menu.row = menu.row.saturating_sub(1);
+    }
+    if keys.just_pressed(keymap::letter_down()) {
+        menu.row = (menu.row + 1).min(ROWS.len() - 1);
+    }
+    if keys.just_pressed(keymap::mute()) {
+        audio.muted = !audio.muted;
+    }
+    if [
+        keymap::audio_menu(),
+        keymap::menu_close(),
+        keymap::confirm(),
+    ]
+    .into_iter()
+    .any(|key| keys.just_pressed(key))
+    {
+        save(&settings, &audio);
+        commands.remove_resource::<AudioMenu>();
+        confirmed.send(MenuConfirmed);
+    }
+    keys.reset_all();
+}
+
+pub fn update_ui(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    audio: Res<AudioSettings>,
+    menu: Option<Res<AudioMenu>>,
+    mut ui: Query<&mut BitmapText, With<AudioMenuUI>>,
+) {
+    let mut value = String::new();
+    if let Some(menu) = menu {
+        value.push_str("VOLUME");
+        let volumes = [audio.master, audio.music, audio.effects];
+        for (row, (name, volume)) in ROWS.iter().zip(volumes).enumerate() {
+            let filled = (volume * SLIDER_WIDTH as f32).round() as usize;
+            write!(
+                value,
+                "\n{} {name:<7} [{}{}] {:>3}%",
+                if row == menu.row { '>' } else { ' ' },
+                "#".repeat(filled),
+                "-".repeat(SLIDER_WIDTH - filled),
+                (volume * 100.0).round()
+            )
+            .expect("writing to a string");
+        }
+        if audio.muted {
+            value.push_str("\nmuted");
+        }
+    }
+    ui.single_mut()
+        .set_if_neq(BitmapText::new(value, themes.current(&settings).ink));
+}
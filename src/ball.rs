@@ -1,10 +1,15 @@
+use std::ops::RangeInclusive;
+
 use bevy::prelude::*;
 
 use crate::{
-    audio::{self},
     bat::{Bat, Direction, Variant},
     consts,
+    events::{BallHit, PointScored, WallBounce},
+    palette::{PaletteSwap, Part},
+    settings::{GameMode, Settings},
     state::{GameState, State},
+    tuning::GameTuning,
 };
 
 #[derive(Component)]
@@ -13,132 +18,253 @@ pub struct Ball {
     pub velocity: Vec2,
     pub last_hit: Variant,
     pub hit_edge: bool,
+    /// Builds up with every hit of the rally, from 0 to 1.
+    pub heat: f32,
 }
 
-impl Default for Ball {
-    fn default() -> Self {
-        let server = Variant::Dark;
+/// Heat from which smashes get stronger, each one passed adds `heat_smash_bonus`.
+pub const HEAT_THRESHOLDS: [f32; 2] = [0.5, 1.0];
+
+/// What the ball ran into during one [`Ball::step`].
+#[derive(Default, Debug)]
+pub struct Contacts {
+    pub hit: Option<BallHit>,
+    pub bounce: Option<WallBounce>,
+}
+
+impl Ball {
+    pub fn served(tuning: &GameTuning, server: &Variant) -> Self {
+        let server = server.clone();
         Self {
             position: Vec2::new(0.0, server.default_y_position()),
-            velocity: Vec2::new(0.0, server.default_y_position() * -0.5),
+            velocity: Vec2::new(0.0, server.default_y_position() * -tuning.serve_speed),
             last_hit: server,
             hit_edge: false,
+            heat: 0.0,
         }
     }
 }
 
-pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let ball = Ball::default();
+impl Ball {
+    /// Puts the ball back in the server's hand.
+    pub fn serve(&mut self, tuning: &GameTuning, server: &Variant) {
+        let served = Self::served(tuning, server);
+        self.position = served.position;
+        self.velocity = served.velocity;
+        self.last_hit = served.last_hit;
+        self.heat = 0.0;
+    }
+
+    /// Heat thresholds passed so far.
+    pub fn heat_level(&self) -> usize {
+        HEAT_THRESHOLDS
+            .iter()
+            .filter(|threshold| self.heat >= **threshold)
+            .count()
+    }
+
+    /// The speed limit grows with the heat of the rally.
+    pub fn max_speed(&self, tuning: &GameTuning) -> f32 {
+        tuning.max_ball_speed * (1.0 + (tuning.heat_max_speed_scale - 1.0) * self.heat)
+    }
+
+    /// Moves the ball for one tick lasting `delta` seconds, bouncing it off the sides,
+    /// the practice wall and swinging bats.
+    pub fn step<'a>(
+        &mut self,
+        bats: impl IntoIterator<Item = &'a Bat>,
+        tuning: &GameTuning,
+        mode: GameMode,
+        edge: f32,
+        delta: f32,
+    ) -> Contacts {
+        let mut contacts = Contacts::default();
+        if !(-edge..=edge).contains(&self.position.x) {
+            if !self.hit_edge {
+                self.hit_edge = true;
+                self.velocity.x *= -1.0;
+                contacts.bounce = Some(WallBounce {
+                    position: self.position,
+                });
+            }
+        } else {
+            self.hit_edge = false;
+        }
+        if mode == GameMode::Practice
+            && self.last_hit == Variant::Light
+            && self.position.y > Variant::Dark.default_y_position()
+        {
+            let max_speed = self.max_speed(tuning);
+            self.velocity.y *= -tuning.wall_return_scale;
+            self.velocity.y = self.velocity.y.clamp(-max_speed, max_speed);
+            self.position.y = Variant::Dark.default_y_position() - 0.1;
+            self.last_hit = Variant::Dark;
+            contacts.bounce = Some(WallBounce {
+                position: self.position,
+            });
+        }
+
+        for bat in bats {
+            if bat.variant == self.last_hit || bat.swinging != Direction::Down {
+                continue;
+            }
+            let diff_x = bat.position_x - self.position.x;
+            let diff_y = bat.variant.default_y_position() - self.position.y;
+            if diff_y.abs() > tuning.hit_radius || diff_x.abs() > tuning.hit_radius {
+                continue;
+            }
+            let max_speed = self.max_speed(tuning);
+            let smash_scale =
+                tuning.hit_y_scale_max + tuning.heat_smash_bonus * self.heat_level() as f32;
+            self.velocity.x = -diff_x * tuning.hit_x_multiplier;
+            self.velocity.y *= -(diff_y.abs() * tuning.hit_y_distance_factor)
+                .clamp(tuning.hit_y_scale_min, smash_scale);
+            self.last_hit = bat.variant.clone();
+            self.velocity.y = self.velocity.y.clamp(-max_speed, max_speed);
+            self.heat = (self.heat + tuning.heat_per_hit).min(1.0);
+            contacts.hit = Some(BallHit {
+                by: bat.variant.clone(),
+                position: self.position,
+                velocity: self.velocity,
+                offset: diff_x,
+            });
+        }
+        self.position += self.velocity * delta;
+        contacts
+    }
+
+    /// Points the ball along its velocity.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_z((self.velocity.x / self.velocity.y).atan())
+    }
+}
+
+impl Default for Ball {
+    fn default() -> Self {
+        Self::served(&GameTuning::default(), &Variant::Dark)
+    }
+}
+
+pub fn arena_edge(window_width: f32) -> f32 {
+    let ball_width = 2.0 * consts::SCALE;
+    (window_width * 0.5 - ball_width * 0.5) / consts::SCALE
+}
+
+/// Where the ball bounces off the sides, follows the window width.
+#[derive(Resource)]
+pub struct Arena {
+    pub edge: f32,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self {
+            edge: arena_edge(Window::default().width()),
+        }
+    }
+}
+
+pub fn update_arena(window: Query<&Window>, mut arena: ResMut<Arena>) {
+    let edge = arena_edge(window.single().width());
+    if arena.edge != edge {
+        arena.edge = edge;
+    }
+}
+
+pub fn playable_range(mode: GameMode) -> RangeInclusive<f32> {
+    match mode {
+        GameMode::Practice => Variant::Light.default_y_position()..=f32::MAX,
+        GameMode::Versus | GameMode::Cpu => {
+            Variant::Light.default_y_position()..=Variant::Dark.default_y_position()
+        }
+    }
+}
+
+/// Predicts where the ball goes if nobody hits it, mirroring the edge bounce in [`update`].
+pub fn trajectory(ball: &Ball, edge: f32, range: RangeInclusive<f32>) -> Vec<Vec2> {
+    const STEP: f32 = 1.0 / 60.0;
+    const STEPS: usize = 600;
+
+    let mut position = ball.position;
+    let mut velocity = ball.velocity;
+    let mut hit_edge = ball.hit_edge;
+    let mut points = vec![position];
+    for _ in 0..STEPS {
+        if !(-edge..=edge).contains(&position.x) {
+            if !hit_edge {
+                hit_edge = true;
+                velocity.x *= -1.0;
+                points.push(position);
+            }
+        } else {
+            hit_edge = false;
+        }
+        position += velocity * STEP;
+        if !range.contains(&position.y) {
+            break;
+        }
+    }
+    points.push(position);
+    points
+}
+
+pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>, tuning: Res<GameTuning>) {
+    let ball = Ball::served(&tuning, &Variant::Dark);
+    let texture = asset_server.load("ball.png");
     commands.spawn((
         SpriteBundle {
-            texture: asset_server.load("ball.png"),
+            texture: texture.clone(),
             transform: Transform::from_scale(Vec3::splat(1.0 * consts::SCALE))
                 .with_translation(Vec3::new(0.0, 0.0, 15.0)),
             ..default()
         },
         ball,
+        PaletteSwap::new(Part::Ball, texture),
     ));
 }
 
+/// Moves the ball and serves it again after a point, only telling the rest of the game what
+/// happened through events.
+#[allow(clippy::too_many_arguments)]
 pub fn update(
-    time: Res<Time>,
-    mut state: Query<&mut State>,
+    fixed_time: Res<FixedTime>,
+    tuning: Res<GameTuning>,
+    settings: Res<Settings>,
+    arena: Res<Arena>,
+    state: Query<&State>,
     mut ball: Query<(&mut Transform, &mut Ball)>,
-    mut bats: Query<&mut Bat>,
-    window: Query<&Window>,
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    bats: Query<&Bat>,
+    mut hits: EventWriter<BallHit>,
+    mut bounces: EventWriter<WallBounce>,
+    mut points: EventWriter<PointScored>,
 ) {
-    let mut state = state.single_mut();
+    let state = state.single();
     if !matches!(state.game_state, GameState::Playing) {
         return;
     }
     let (mut transform, mut ball) = ball.single_mut();
-    let window = window.single();
-
-    let ball_width = 2.0 * consts::SCALE;
-    let edge = (window.width() * 0.5 - ball_width * 0.5) / consts::SCALE;
 
-    if !(edge * -1.0..=edge).contains(&ball.position.x) {
-        if !ball.hit_edge {
-            ball.hit_edge = true;
-            ball.velocity.x *= -1.0;
-        }
-    } else {
-        ball.hit_edge = false;
+    let contacts = ball.step(
+        bats.iter(),
+        &tuning,
+        settings.mode,
+        arena.edge,
+        fixed_time.period.as_secs_f32(),
+    );
+    if let Some(bounce) = contacts.bounce {
+        bounces.send(bounce);
     }
-    for bat in &mut bats {
-        #[cfg(feature = "singleplayer")]
-        {
-            match &ball.last_hit {
-                Variant::Light => {
-                    if ball.position.y > Variant::Dark.default_y_position() {
-                        ball.velocity.y *= -1.25;
-                        ball.velocity.y = ball.velocity.y.clamp(-64.0, 64.0);
-                        ball.position.y = Variant::Dark.default_y_position() - 0.1;
-                        ball.last_hit = Variant::Dark;
-                    }
-                    continue;
-                }
-                _ => {}
-            }
-        }
-
-        match (&bat.variant, &ball.last_hit) {
-            (Variant::Dark, Variant::Dark) => continue,
-            (Variant::Light, Variant::Light) => continue,
-            _ => {}
-        };
-
-        if bat.swinging != Direction::Down {
-            continue;
-        }
-        let diff_x = bat.position_x - ball.position.x;
-        let diff_y = bat.variant.default_y_position() - ball.position.y;
-        if diff_y.abs() > 3.95 || diff_x.abs() > 3.95 {
-            continue;
-        }
-        ball.velocity.x = -diff_x * 4.0;
-        ball.velocity.y *= -(diff_y.abs() * 0.4).clamp(0.9, 1.25);
-        ball.last_hit = bat.variant.clone();
-        ball.velocity.y = ball.velocity.y.clamp(-64.0, 64.0);
-
-        state.hits_with_velocity += ball.velocity.x.abs() + ball.velocity.y.abs();
-
-        audio::spawn_hit_sound(&mut commands, &asset_server);
+    if let Some(hit) = contacts.hit {
+        hits.send(hit);
     }
-    let offset = ball.velocity * Vec2::splat(time.delta_seconds());
-    ball.position += offset;
     transform.translation.x = ball.position.x * consts::SCALE;
     transform.translation.y = ball.position.y * consts::SCALE;
-    let angle = (ball.velocity.x / ball.velocity.y).atan();
-    transform.rotation = Quat::from_rotation_z(angle);
-
-    #[cfg(not(feature = "singleplayer"))]
-    let playable_range = Variant::Light.default_y_position()..=Variant::Dark.default_y_position();
-
-    #[cfg(feature = "singleplayer")]
-    let playable_range = Variant::Light.default_y_position()..=f32::MAX;
-
-    if !(playable_range).contains(&ball.position.y) {
-        info!(
-            "score: {} in {}s",
-            state.hits_with_velocity,
-            state.game_time.elapsed_secs()
-        );
-        state.game_over(GameState::Winner(ball.last_hit.clone()));
-        let Ball {
-            position,
-            velocity,
-            last_hit,
-            hit_edge: _,
-        } = Ball::default();
-        ball.position = position;
-        ball.velocity = velocity;
-        ball.last_hit = last_hit;
-        for mut bat in &mut bats {
-            bat.swinging = Direction::None;
-            bat.position_x = 0.0;
-        }
+    transform.rotation = ball.rotation();
+
+    // served again by [`crate::state::score`], once it knows who serves next
+    if !playable_range(settings.mode).contains(&ball.position.y) {
+        points.send(PointScored {
+            winner: ball.last_hit.clone(),
+        });
     }
 }
@@ -1,10 +1,15 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, time::Duration};
 
 use bevy::{prelude::*, time::Stopwatch};
+use serde::Serialize;
 
 use crate::{
+    ai::Cpu,
     consts, keymap,
+    palette::{PaletteSwap, Part},
+    settings::Settings,
     state::{GameState, State},
+    tuning::GameTuning,
 };
 
 #[derive(Component)]
@@ -12,19 +17,25 @@ pub struct BatDebounce {
     pub time: Stopwatch,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum Variant {
     Light,
     Dark,
 }
 
 impl Variant {
-    fn default_rotation(&self) -> Quat {
+    pub fn default_rotation(&self) -> Quat {
         match self {
             Variant::Light => Quat::from_rotation_z(0.0),
             Variant::Dark => Quat::from_rotation_z(PI),
         }
     }
+    pub fn opponent(&self) -> Variant {
+        match self {
+            Variant::Light => Variant::Dark,
+            Variant::Dark => Variant::Light,
+        }
+    }
     pub fn default_y_position(&self) -> f32 {
         match self {
             Variant::Light => -20.0,
@@ -33,7 +44,7 @@ impl Variant {
     }
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, PartialEq)]
 pub enum Direction {
     Up,
     Down,
@@ -48,83 +59,172 @@ pub struct Bat {
     pub position_x: f32,
 }
 
-pub fn spawn(commands: &mut Commands, asset_server: &Res<AssetServer>, variant: Variant) {
+impl Bat {
+    pub fn new(variant: Variant, tuning: &GameTuning) -> Self {
+        Self {
+            variant,
+            swinging: Direction::None,
+            animation_timer: Timer::from_seconds(tuning.swing_cooldown, TimerMode::Repeating),
+            position_x: 0.0,
+        }
+    }
+
+    /// Moves or swings the bat for one tick lasting `delta`.
+    pub fn step(&mut self, input: &BatInput, tuning: &GameTuning, delta: Duration) {
+        self.animation_timer
+            .set_duration(Duration::from_secs_f32(tuning.swing_cooldown));
+        match self.swinging {
+            Direction::None => {
+                if input.swing {
+                    self.swinging = Direction::Down;
+                    return;
+                }
+                let move_speed = if input.run {
+                    tuning.run_speed
+                } else {
+                    tuning.move_speed
+                };
+                if input.left {
+                    self.position_x -= delta.as_secs_f32() * move_speed;
+                }
+                if input.right {
+                    self.position_x += delta.as_secs_f32() * move_speed;
+                }
+                self.position_x = self
+                    .position_x
+                    .clamp(-consts::SCALE * 12.0, consts::SCALE * 12.0);
+            }
+            Direction::Down => {
+                self.animation_timer.tick(delta);
+                if self.animation_timer.just_finished() {
+                    self.swinging = Direction::Up;
+                }
+            }
+            Direction::Up => {
+                self.animation_timer.tick(delta);
+                if self.animation_timer.just_finished() {
+                    self.swinging = Direction::None;
+                }
+            }
+        }
+    }
+
+    /// Tilts the bat forward while swinging down and back while swinging up.
+    pub fn rotation(&self) -> Quat {
+        let elapsed = self.animation_timer.elapsed_secs();
+        let duration = self.animation_timer.duration().as_secs_f32();
+        let angle = match self.swinging {
+            Direction::None => 0.0,
+            Direction::Down => -PI * 4.0 * elapsed,
+            Direction::Up => -PI * 4.0 * (duration - elapsed),
+        };
+        Quat::from_rotation_x(angle) * self.variant.default_rotation()
+    }
+
+    pub fn reset(&mut self) {
+        self.swinging = Direction::None;
+        self.position_x = 0.0;
+        self.animation_timer.reset();
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct BatInput {
+    pub left: bool,
+    pub right: bool,
+    pub run: bool,
+    pub swing: bool,
+}
+
+#[derive(Component)]
+pub enum Controller {
+    Keyboard,
+    Cpu(Cpu),
+    Replay,
+}
+
+pub fn spawn(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    tuning: &GameTuning,
+    variant: Variant,
+    controller: Controller,
+) {
     let texture = match variant {
         Variant::Light => "bat_light.png",
         Variant::Dark => "bat_dark.png",
     };
     let position = Vec3::new(0.0, variant.default_y_position() * consts::SCALE, 10.0);
-    let bat = Bat {
-        variant,
-        swinging: Direction::None,
-        animation_timer: Timer::from_seconds(consts::SWING_COOLDOWN, TimerMode::Repeating),
-        position_x: 0.0,
-    };
+    let bat = Bat::new(variant, tuning);
+    let texture = asset_server.load(texture);
     commands.spawn((
         SpriteBundle {
-            texture: asset_server.load(texture),
+            texture: texture.clone(),
             transform: Transform::from_scale(Vec3::splat(1.0 * consts::SCALE))
                 .with_translation(position)
                 .with_rotation(bat.variant.default_rotation()),
             ..default()
         },
+        PaletteSwap::new(Part::Bat(bat.variant.clone()), texture),
         bat,
+        controller,
+        BatInput::default(),
     ));
 }
 
+/// Spawns a bat for everyone playing, driven by the replay, the cpu or the keyboard.
+pub fn spawn_players(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    tuning: &GameTuning,
+    settings: &Settings,
+    replay: bool,
+) {
+    for variant in [Variant::Dark, Variant::Light] {
+        if !settings.has_player(&variant) {
+            continue;
+        }
+        let controller = if replay {
+            Controller::Replay
+        } else if settings.is_cpu(&variant) {
+            Controller::Cpu(Cpu::new(settings.difficulty))
+        } else {
+            Controller::Keyboard
+        };
+        spawn(commands, asset_server, tuning, variant, controller);
+    }
+}
+
+pub fn read_input(keys: Res<Input<KeyCode>>, mut bats: Query<(&Bat, &Controller, &mut BatInput)>) {
+    for (bat, controller, mut input) in &mut bats {
+        if !matches!(controller, Controller::Keyboard) {
+            continue;
+        }
+        // swings are kept until a tick has used them
+        *input = BatInput {
+            left: keys.pressed(keymap::left(&bat.variant)),
+            right: keys.pressed(keymap::right(&bat.variant)),
+            run: keys.pressed(keymap::run(&bat.variant)),
+            swing: input.swing || keys.just_pressed(keymap::swing(&bat.variant)),
+        };
+    }
+}
+
 pub fn update(
-    time: Res<Time>,
-    keys: Res<Input<KeyCode>>,
-    mut bat: Query<(&mut Transform, &mut Bat)>,
+    fixed_time: Res<FixedTime>,
+    tuning: Res<GameTuning>,
+    mut bat: Query<(&mut Transform, &mut Bat, &mut BatInput)>,
     state: Query<&State>,
 ) {
     let state = state.single();
-    if !matches!(state.game_state, GameState::Playing) {
-        return;
-    }
-    for (mut transform, mut bat) in &mut bat {
-        match bat.swinging {
-            Direction::None => {
-                if keys.just_pressed(keymap::swing(&bat.variant)) {
-                    bat.swinging = Direction::Down;
-                    continue;
-                }
-                let move_speed = if keys.pressed(keymap::run(&bat.variant)) {
-                    consts::RUN_SPEED
-                } else {
-                    consts::MOVE_SPEED
-                };
-                if keys.pressed(keymap::left(&bat.variant)) {
-                    bat.position_x -= time.delta_seconds() * move_speed;
-                }
-                if keys.pressed(keymap::right(&bat.variant)) {
-                    bat.position_x += time.delta_seconds() * move_speed;
-                }
-                bat.position_x = bat
-                    .position_x
-                    .clamp(-consts::SCALE * 12.0, consts::SCALE * 12.0);
-                let offset = Vec3::new(
-                    bat.position_x * consts::SCALE,
-                    transform.translation.y,
-                    transform.translation.z,
-                );
-                transform.translation = offset;
-            }
-            Direction::Up => {
-                transform.rotate_x(PI * 4.0 * time.delta().as_secs_f32());
-                bat.animation_timer.tick(time.delta());
-                if bat.animation_timer.just_finished() {
-                    transform.rotation = bat.variant.default_rotation();
-                    bat.swinging = Direction::None;
-                }
-            }
-            Direction::Down => {
-                transform.rotate_x(-PI * 4.0 * time.delta().as_secs_f32());
-                bat.animation_timer.tick(time.delta());
-                if bat.animation_timer.just_finished() {
-                    bat.swinging = Direction::Up;
-                }
-            }
-        };
+    let playing = matches!(state.game_state, GameState::Playing);
+    for (mut transform, mut bat, mut input) in &mut bat {
+        let swing = std::mem::take(&mut input.swing);
+        if !playing {
+            continue;
+        }
+        bat.step(&BatInput { swing, ..*input }, &tuning, fixed_time.period);
+        transform.translation.x = bat.position_x * consts::SCALE;
+        transform.rotation = bat.rotation();
     }
 }
@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+use bevy::{prelude::*, window::WindowMode};
+use clap::Parser;
+
+use crate::settings::{Difficulty, GameMode, MatchFormat, Settings};
+
+#[derive(Parser)]
+#[command(version, about = "table tennis game with bevy")]
+pub struct Cli {
+    /// Who plays, defaults to practice when built with the singleplayer feature
+    #[arg(long, value_enum)]
+    pub mode: Option<GameMode>,
+    /// How well the cpu plays
+    #[arg(long, value_enum, default_value = "normal")]
+    pub difficulty: Difficulty,
+    /// Points needed to win a game
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub points: u32,
+    /// Lead needed to win a game
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub win_by: u32,
+    /// Games in a match, the first to win more than half wins the match
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub best_of: u32,
+    /// Window width in logical pixels
+    #[arg(long)]
+    pub width: Option<f32>,
+    /// Window height in logical pixels
+    #[arg(long)]
+    pub height: Option<f32>,
+    #[arg(long)]
+    pub fullscreen: bool,
+    #[arg(long)]
+    pub mute: bool,
+    /// Seed for the cpu players, random when left out
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Let the cpu play this many matches without a window and print the results
+    #[arg(long, value_name = "MATCHES")]
+    pub headless: Option<usize>,
+    /// Give up on a headless match still going after this many seconds of play
+    #[arg(long, value_name = "SECONDS", default_value_t = 1800)]
+    pub max_match_seconds: u32,
+    /// Play back a recorded match
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+    /// Where finished matches are recorded
+    #[arg(long, value_name = "DIR", default_value = "replays")]
+    pub replay_dir: PathBuf,
+    /// Don't record finished matches
+    #[arg(long)]
+    pub no_record: bool,
+    /// Write finished matches as json and append them to results.csv in this directory
+    #[arg(long, value_name = "DIR")]
+    pub export_dir: Option<PathBuf>,
+    /// Where the high score table is kept
+    #[arg(long, value_name = "FILE", default_value = "highscores.ron")]
+    pub high_scores: PathBuf,
+    /// Where the volume settings are kept
+    #[arg(long, value_name = "FILE", default_value = "audio.ron")]
+    pub audio_settings: PathBuf,
+    /// Where the player names and colors are kept
+    #[arg(long, value_name = "FILE", default_value = "players.ron")]
+    pub player_settings: PathBuf,
+}
+
+impl Cli {
+    pub fn settings(&self) -> Settings {
+        Settings {
+            mode: self.mode.unwrap_or_default(),
+            difficulty: self.difficulty,
+            format: MatchFormat {
+                points_per_game: self.points,
+                win_by: self.win_by,
+                best_of: self.best_of,
+            },
+            seed: self.seed,
+            muted: self.mute,
+            autoplay: false,
+            title_screen: false,
+            countdown: false,
+            theme: 0,
+            replay_dir: (!self.no_record).then(|| self.replay_dir.clone()),
+            high_scores: Some(self.high_scores.clone()),
+            export_dir: self.export_dir.clone(),
+            audio: Some(self.audio_settings.clone()),
+            players: Some(self.player_settings.clone()),
+        }
+    }
+
+    pub fn window(&self) -> Window {
+        let mut window = Window {
+            title: "bordtennis".into(),
+            fit_canvas_to_parent: true,
+            ..default()
+        };
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            window.resolution.set(width, height);
+        } else if let Some(width) = self.width {
+            window.resolution.set(width, window.resolution.height());
+        } else if let Some(height) = self.height {
+            window.resolution.set(window.resolution.width(), height);
+        }
+        if self.fullscreen {
+            window.mode = WindowMode::BorderlessFullscreen;
+        }
+        window
+    }
+}
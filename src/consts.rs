@@ -1,5 +1,5 @@
+use std::time::Duration;
+
 pub const SCALE: f32 = 12.0;
-pub const MOVE_SPEED: f32 = 24.0;
-pub const RUN_SPEED: f32 = MOVE_SPEED * 2.5;
-pub const SWING_COOLDOWN: f32 = 0.1;
+pub const TICK: Duration = Duration::from_nanos(16_666_667);
 pub const SCORE_ANIMATION_OFFSET: f32 = 4.0;
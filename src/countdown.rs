@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::{
+    consts,
+    state::{GameState, State},
+};
+
+/// `countdown.png` has the numbers 1 to 9, longer countdowns stay on 9 until they get there.
+const NUMBERS: u32 = 9;
+/// How much bigger a number is when it first shows, shrinking back over its second.
+const POP: f32 = 0.5;
+
+#[derive(Component)]
+pub struct CountdownUI;
+
+pub fn spawn(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let texture_handle = asset_server.load("countdown.png");
+    let texture_atlas = TextureAtlas::from_grid(
+        texture_handle,
+        Vec2::new(24.0, 32.0),
+        1,
+        NUMBERS as usize,
+        None,
+        None,
+    );
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: texture_atlases.add(texture_atlas),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 200.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        CountdownUI,
+    ));
+}
+
+/// Which sprite shows `number`.
+pub fn sprite_index(number: u32) -> usize {
+    (number.clamp(1, NUMBERS) - 1) as usize
+}
+
+/// Shows the number counted down to, popping in as it changes.
+pub fn update(
+    state: Query<&State>,
+    mut countdown_ui: Query<
+        (&mut TextureAtlasSprite, &mut Transform, &mut Visibility),
+        With<CountdownUI>,
+    >,
+) {
+    let state = state.single();
+    let (mut sprite, mut transform, mut visibility) = countdown_ui.single_mut();
+    if !matches!(state.game_state, GameState::Countdown { .. }) {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+    visibility.set_if_neq(Visibility::Inherited);
+    let number = state.countdown_number();
+    sprite.index = sprite_index(number);
+    let remaining = state.countdown.remaining_secs();
+    let pop = 1.0 + POP * (remaining - remaining.floor());
+    transform.scale = Vec3::splat(pop * consts::SCALE);
+}
@@ -0,0 +1,270 @@
+use bevy::{input::InputSystem, prelude::*, reflect::GetPath, sprite::Anchor};
+
+use crate::{
+    ball::{self, Arena, Ball},
+    bat::{self, Bat, Direction, Variant},
+    consts, keymap,
+    settings::Settings,
+    state::State,
+    tuning::GameTuning,
+};
+
+const DEBUG_FONT_SIZE: f32 = 18.0;
+const VELOCITY_LINE_SECONDS: f32 = 0.5;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .init_resource::<Console>()
+            .add_systems(Startup, spawn)
+            .add_systems(
+                PreUpdate,
+                console_input.after(InputSystem).before(bat::read_input),
+            )
+            .add_systems(Update, (toggle_overlay, draw_overlay, update_text));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    pub line: String,
+    pub output: String,
+}
+
+#[derive(Component)]
+pub struct DebugText;
+
+fn parse_variant(value: &str) -> Result<Variant, String> {
+    match value {
+        "light" => Ok(Variant::Light),
+        "dark" => Ok(Variant::Dark),
+        _ => Err(format!("unknown player '{value}', expected light or dark")),
+    }
+}
+
+fn parse_number(value: Option<&str>) -> Result<f32, String> {
+    let value = value.ok_or("missing number")?;
+    value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))
+}
+
+/// Runs a single console command, returning the line to show as output.
+pub fn execute(
+    command: &str,
+    ball: &mut Ball,
+    state: &mut State,
+    tuning: &mut GameTuning,
+) -> Result<String, String> {
+    let mut words = command.split_whitespace();
+    match words.next() {
+        Some("help") => {
+            Ok("velocity <x> <y> | speed <n> | server <light|dark> | tune <field> <value>".into())
+        }
+        Some("velocity") => {
+            let x = parse_number(words.next())?;
+            let y = parse_number(words.next())?;
+            ball.velocity = Vec2::new(x, y);
+            Ok(format!("velocity = {:?}", ball.velocity))
+        }
+        Some("speed") => {
+            let speed = parse_number(words.next())?;
+            ball.velocity = ball.velocity.normalize_or_zero() * speed;
+            Ok(format!("velocity = {:?}", ball.velocity))
+        }
+        Some("server") => {
+            let server = parse_variant(words.next().unwrap_or_default())?;
+            state.server = server;
+            Ok(format!("server = {:?}", state.server))
+        }
+        Some("tune") => {
+            let field = words.next().ok_or("missing tuning field")?;
+            let value = parse_number(words.next())?;
+            let target = tuning
+                .path_mut::<f32>(field)
+                .map_err(|_| format!("unknown tuning field '{field}'"))?;
+            *target = value;
+            Ok(format!("{field} = {value}"))
+        }
+        Some(command) => Err(format!("unknown command '{command}', try help")),
+        None => Ok(String::new()),
+    }
+}
+
+pub fn spawn(mut commands: Commands) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: DEBUG_FONT_SIZE,
+                    color: Color::BLACK,
+                    ..default()
+                },
+            ),
+            text_anchor: Anchor::BottomLeft,
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 200.0)),
+            ..default()
+        },
+        DebugText,
+    ));
+}
+
+fn console_input(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+    mut ball: Query<&mut Ball>,
+    mut state: Query<&mut State>,
+    mut tuning: ResMut<GameTuning>,
+) {
+    if keys.just_pressed(keymap::console()) {
+        console.open = !console.open;
+        console.line.clear();
+        keys.reset_all();
+        characters.clear();
+        return;
+    }
+    if !console.open {
+        characters.clear();
+        return;
+    }
+    for event in characters.iter() {
+        if !event.char.is_control() && event.char != '`' {
+            console.line.push(event.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        console.line.pop();
+    }
+    if keys.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.line);
+        let mut ball = ball.single_mut();
+        let mut state = state.single_mut();
+        console.output = match execute(&line, &mut ball, &mut state, &mut tuning) {
+            Ok(output) => output,
+            Err(error) => format!("error: {error}"),
+        };
+    }
+    keys.reset_all();
+}
+
+fn toggle_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keys.just_pressed(keymap::debug_overlay()) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_overlay(
+    mut gizmos: Gizmos,
+    overlay: Res<DebugOverlay>,
+    tuning: Res<GameTuning>,
+    settings: Res<Settings>,
+    arena: Res<Arena>,
+    ball: Query<&Ball>,
+    bats: Query<&Bat>,
+    window: Query<&Window>,
+) {
+    if !overlay.visible {
+        return;
+    }
+    let window = window.single();
+    let edge = arena.edge;
+    let range = ball::playable_range(settings.mode);
+    let top = range.end().min(window.height() * 0.5 / consts::SCALE);
+    let bottom = *range.start();
+
+    for x in [-edge, edge] {
+        gizmos.line_2d(
+            Vec2::new(x, bottom) * consts::SCALE,
+            Vec2::new(x, top) * consts::SCALE,
+            Color::GRAY,
+        );
+    }
+    for y in [bottom, top] {
+        gizmos.line_2d(
+            Vec2::new(-edge, y) * consts::SCALE,
+            Vec2::new(edge, y) * consts::SCALE,
+            Color::GRAY,
+        );
+    }
+
+    for bat in &bats {
+        let color = if bat.swinging == Direction::Down {
+            Color::GREEN
+        } else {
+            Color::DARK_GREEN
+        };
+        gizmos.rect_2d(
+            Vec2::new(bat.position_x, bat.variant.default_y_position()) * consts::SCALE,
+            0.0,
+            Vec2::splat(tuning.hit_radius * 2.0) * consts::SCALE,
+            color,
+        );
+    }
+
+    for ball in &ball {
+        gizmos.linestrip_2d(
+            ball::trajectory(ball, edge, range.clone())
+                .into_iter()
+                .map(|point| point * consts::SCALE),
+            Color::BLUE,
+        );
+        gizmos.line_2d(
+            ball.position * consts::SCALE,
+            (ball.position + ball.velocity * VELOCITY_LINE_SECONDS) * consts::SCALE,
+            Color::RED,
+        );
+    }
+}
+
+fn update_text(
+    overlay: Res<DebugOverlay>,
+    console: Res<Console>,
+    ball: Query<&Ball>,
+    bats: Query<&Bat>,
+    window: Query<&Window>,
+    mut text: Query<(&mut Text, &mut Transform), With<DebugText>>,
+) {
+    let (mut text, mut transform) = text.single_mut();
+    let window = window.single();
+    let padding = 0.5 * consts::SCALE;
+    transform.translation.x = window.width() * -0.5 + padding;
+    transform.translation.y = window.height() * -0.5 + padding;
+
+    let mut lines = Vec::new();
+    if overlay.visible {
+        for ball in &ball {
+            lines.push(format!(
+                "velocity: ({:.2}, {:.2}) |{:.2}|",
+                ball.velocity.x,
+                ball.velocity.y,
+                ball.velocity.length()
+            ));
+            lines.push(format!("last_hit: {:?}", ball.last_hit));
+            lines.push(format!("hit_edge: {}", ball.hit_edge));
+        }
+        for bat in &bats {
+            lines.push(format!(
+                "{:?} swinging: {:?} x: {:.2}",
+                bat.variant, bat.swinging, bat.position_x
+            ));
+        }
+    }
+    if console.open {
+        if !console.output.is_empty() {
+            lines.push(console.output.clone());
+        }
+        lines.push(format!("> {}_", console.line));
+    }
+    text.sections[0].value = lines.join("\n");
+}
@@ -0,0 +1,105 @@
+use std::{cmp::Reverse, time::Duration};
+
+use bevy::{asset::ChangeWatcher, prelude::*, sprite::Anchor, text::TextAlignment};
+
+use crate::{
+    consts,
+    debug::DebugPlugin,
+    tuning::{GameTuning, TuningLoadFailed},
+};
+
+const TOAST_SECONDS: f32 = 4.0;
+const TOAST_FONT_SIZE: f32 = 20.0;
+
+pub fn change_watcher() -> Option<ChangeWatcher> {
+    ChangeWatcher::with_delay(Duration::from_millis(200))
+}
+
+pub struct DevPlugin;
+
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(DebugPlugin)
+            .add_systems(Update, (toast_reloads, update_toasts).chain());
+    }
+}
+
+#[derive(Component)]
+pub struct Toast {
+    timer: Timer,
+}
+
+fn spawn_toast(commands: &mut Commands, message: String, color: Color) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                message,
+                TextStyle {
+                    font_size: TOAST_FONT_SIZE,
+                    color,
+                    ..default()
+                },
+            )
+            .with_alignment(TextAlignment::Left),
+            text_anchor: Anchor::TopLeft,
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 200.0)),
+            ..default()
+        },
+        Toast {
+            timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+        },
+    ));
+}
+
+fn toast_reloads(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut tuning_events: EventReader<AssetEvent<GameTuning>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut failed: EventReader<TuningLoadFailed>,
+) {
+    for event in tuning_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            let path = asset_server
+                .get_handle_path(handle)
+                .map(|path| path.path().display().to_string())
+                .unwrap_or_else(|| "tuning".into());
+            spawn_toast(&mut commands, format!("reloaded {path}"), Color::BLACK);
+        }
+    }
+    for event in image_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            let Some(path) = asset_server.get_handle_path(handle) else {
+                continue;
+            };
+            let message = format!("reloaded {}", path.path().display());
+            spawn_toast(&mut commands, message, Color::BLACK);
+        }
+    }
+    for TuningLoadFailed(message) in failed.iter() {
+        spawn_toast(&mut commands, message.clone(), Color::MAROON);
+    }
+}
+
+fn update_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    window: Query<&Window>,
+    mut toasts: Query<(Entity, &mut Toast, &mut Transform)>,
+) {
+    let window = window.single();
+    let padding = 0.5 * consts::SCALE;
+    let mut toasts: Vec<_> = toasts.iter_mut().collect();
+    toasts.sort_by_key(|(_, toast, _)| Reverse(toast.timer.elapsed()));
+    let mut y = window.height() * 0.5 - padding;
+    for (entity, mut toast, mut transform) in toasts {
+        toast.timer.tick(time.delta());
+        if toast.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.x = window.width() * -0.5 + padding;
+        transform.translation.y = y;
+        y -= TOAST_FONT_SIZE + padding;
+    }
+}
@@ -0,0 +1,73 @@
+//! Everything that happens during a match and in the menus, sent as Bevy events so audio, the UI,
+//! stats and anything outside the crate can follow the game without touching the physics.
+
+use bevy::prelude::*;
+
+use crate::bat::Variant;
+
+/// A bat returned the ball at `position`, `offset` is how far from the bat's center it was hit.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct BallHit {
+    pub by: Variant,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub offset: f32,
+}
+
+/// The ball bounced off a side of the arena or the practice wall.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct WallBounce {
+    pub position: Vec2,
+}
+
+/// A point started with the ball in the server's hand.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct Served {
+    pub by: Variant,
+}
+
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct PointScored {
+    pub winner: Variant,
+}
+
+/// Sent on the tick the deciding point of a match is scored, right after its [`PointScored`].
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct MatchOver {
+    pub winner: Variant,
+}
+
+/// `by` won `points` points in a row, sent by the stats from the second one on.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct PointStreak {
+    pub by: Variant,
+    pub points: u32,
+}
+
+/// A smash faster than any shot before it in this match.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct FastestShot {
+    pub by: Variant,
+    pub speed: f32,
+}
+
+/// The countdown before play reached `number`, sent once for every number shown.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct CountdownTick {
+    pub number: u32,
+}
+
+/// The player paused a match in progress.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct Paused;
+
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct Resumed;
+
+/// The selection or a value changed in a menu.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct MenuMoved;
+
+/// A menu was opened, closed or its choice confirmed.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct MenuConfirmed;
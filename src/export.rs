@@ -0,0 +1,316 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    bat::Variant,
+    events::MatchOver,
+    players::PlayerSettings,
+    settings::{Difficulty, GameMode, Settings},
+    state::{Score, State},
+    stats::{MatchStats, PlayerStats},
+};
+#[cfg(target_arch = "wasm32")]
+use crate::{
+    consts,
+    text::{self, BitmapText, Pointer},
+    theme::Themes,
+};
+#[cfg(target_arch = "wasm32")]
+use bevy::sprite::Anchor;
+
+pub const CSV_FILE: &str = "results.csv";
+const CSV_HEADER: &str =
+    "timestamp,mode,difficulty,light,dark,winner,games_light,games_dark,game_scores,duration";
+const CSV_STATS: [&str; 7] = [
+    "hits",
+    "longest_rally",
+    "fastest_shot",
+    "average_offset",
+    "smashes",
+    "misses",
+    "distance",
+];
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Players<T> {
+    pub light: T,
+    pub dark: T,
+}
+
+/// Everything written out about a finished match.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MatchReport {
+    /// Seconds since the unix epoch when the match ended.
+    pub timestamp: u64,
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    pub players: Players<String>,
+    pub winner: Variant,
+    pub games: Score,
+    pub game_scores: Vec<Score>,
+    pub duration: f32,
+    pub stats: Players<PlayerStats>,
+}
+
+#[derive(Resource, Default)]
+pub struct Reports {
+    /// The last finished match, offered for download on the web.
+    pub last: Option<MatchReport>,
+    exported: usize,
+}
+
+/// The name a player entered, or who stood in for them.
+pub fn player_name(settings: &Settings, players: &PlayerSettings, variant: &Variant) -> String {
+    if !settings.has_player(variant) {
+        return "wall".into();
+    }
+    if settings.is_cpu(variant) {
+        return format!("cpu ({:?})", settings.difficulty).to_lowercase();
+    }
+    players.name(settings, variant).to_string()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn unix_time() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl MatchReport {
+    pub fn new(
+        settings: &Settings,
+        players: &PlayerSettings,
+        state: &State,
+        stats: &MatchStats,
+        winner: Variant,
+    ) -> Self {
+        Self {
+            timestamp: unix_time(),
+            mode: settings.mode,
+            difficulty: settings.difficulty,
+            players: Players {
+                light: player_name(settings, players, &Variant::Light),
+                dark: player_name(settings, players, &Variant::Dark),
+            },
+            winner,
+            games: state.games,
+            game_scores: state.game_scores.clone(),
+            duration: state.game_time.elapsed_secs(),
+            stats: Players {
+                light: stats.light.clone(),
+                dark: stats.dark.clone(),
+            },
+        }
+    }
+
+    /// `index` tells apart matches ending in the same second.
+    pub fn file_name(&self, index: usize) -> String {
+        format!("match-{}-{index}.json", self.timestamp)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("reports are always valid json")
+    }
+
+    pub fn csv_header() -> String {
+        let mut header = CSV_HEADER.to_string();
+        for player in ["light", "dark"] {
+            for stat in CSV_STATS {
+                write!(header, ",{player}_{stat}").expect("writing to a string");
+            }
+        }
+        header
+    }
+
+    pub fn csv_row(&self) -> String {
+        let game_scores: Vec<String> = self
+            .game_scores
+            .iter()
+            .map(|score| format!("{}-{}", score.light, score.dark))
+            .collect();
+        let mut row = [
+            self.timestamp.to_string(),
+            format!("{:?}", self.mode).to_lowercase(),
+            format!("{:?}", self.difficulty).to_lowercase(),
+            csv_field(&self.players.light),
+            csv_field(&self.players.dark),
+            format!("{:?}", self.winner).to_lowercase(),
+            self.games.light.to_string(),
+            self.games.dark.to_string(),
+            game_scores.join(" "),
+            format!("{:.2}", self.duration),
+        ]
+        .join(",");
+        for stats in [&self.stats.light, &self.stats.dark] {
+            write!(
+                row,
+                ",{},{},{:.2},{:.3},{},{},{:.2}",
+                stats.hits,
+                stats.longest_rally,
+                stats.fastest_shot,
+                stats.average_offset(),
+                stats.smashes,
+                stats.misses,
+                stats.distance
+            )
+            .expect("writing to a string");
+        }
+        row
+    }
+
+    /// Writes the report as json and appends it to the csv in `dir`, starting the csv if needed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, dir: &std::path::Path, index: usize) -> Result<(), String> {
+        use std::io::Write;
+
+        std::fs::create_dir_all(dir)
+            .map_err(|error| format!("could not create {}: {error}", dir.display()))?;
+        let json_path = dir.join(self.file_name(index));
+        std::fs::write(&json_path, self.to_json())
+            .map_err(|error| format!("could not write {}: {error}", json_path.display()))?;
+
+        let csv_path = dir.join(CSV_FILE);
+        let new = !csv_path.exists();
+        let mut csv = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&csv_path)
+            .map_err(|error| format!("could not open {}: {error}", csv_path.display()))?;
+        if new {
+            writeln!(csv, "{}", Self::csv_header())
+                .map_err(|error| format!("could not write {}: {error}", csv_path.display()))?;
+        }
+        writeln!(csv, "{}", self.csv_row())
+            .map_err(|error| format!("could not write {}: {error}", csv_path.display()))
+    }
+}
+
+/// Builds the report once a match is over, writing it out when an export directory is set.
+pub fn export(
+    settings: Res<Settings>,
+    players: Res<PlayerSettings>,
+    stats: Res<MatchStats>,
+    mut reports: ResMut<Reports>,
+    mut match_over: EventReader<MatchOver>,
+    state: Query<&State>,
+) {
+    let state = state.single();
+    for over in match_over.iter() {
+        let report = MatchReport::new(&settings, &players, state, &stats, over.winner.clone());
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = &settings.export_dir {
+            match report.save(dir, reports.exported) {
+                Ok(()) => info!("exported match results to {}", dir.display()),
+                Err(error) => error!("{error}"),
+            }
+            reports.exported += 1;
+        }
+        reports.last = Some(report);
+    }
+}
+
+#[derive(Component)]
+pub struct DownloadButton;
+
+#[cfg(target_arch = "wasm32")]
+pub fn spawn_download_button(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle {
+            transform: Transform::from_scale(Vec3::splat(text::SMALL_PIXEL))
+                .with_translation(Vec3::new(0.0, 0.0, 200.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        BitmapText::new("download results", text::INK).with_anchor(Anchor::BottomRight),
+        DownloadButton,
+    ));
+}
+
+/// Saves `contents` through the browser's download prompt.
+#[cfg(target_arch = "wasm32")]
+fn download(file_name: &str, contents: &str) -> Result<(), String> {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("application/json");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|_| "could not create the download".to_string())?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|_| "could not create the download".to_string())?;
+    let anchor: web_sys::HtmlAnchorElement = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document to download from")?
+        .create_element("a")
+        .ok()
+        .and_then(|element| element.dyn_into().ok())
+        .ok_or("could not create a download link")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).ok();
+    Ok(())
+}
+
+/// Shows the download button on the results screen of a finished match.
+#[cfg(target_arch = "wasm32")]
+#[allow(clippy::too_many_arguments)]
+pub fn download_button(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    reports: Res<Reports>,
+    pointer: Pointer,
+    state: Query<&State>,
+    window: Query<&Window>,
+    mut button: Query<
+        (
+            &mut BitmapText,
+            &mut Transform,
+            &GlobalTransform,
+            &mut Visibility,
+        ),
+        With<DownloadButton>,
+    >,
+) {
+    let state = state.single();
+    let window = window.single();
+    let (mut text, mut transform, global_transform, mut visibility) = button.single_mut();
+    let padding = 0.5 * consts::SCALE;
+    transform.translation.x = window.width() * 0.5 - padding;
+    transform.translation.y = window.height() * -0.5 + padding;
+    let ink = themes.current(&settings).ink;
+    if text.sections[0].color != ink {
+        text.sections[0].color = ink;
+    }
+
+    let report = reports.last.as_ref().filter(|_| state.match_over);
+    *visibility = match report {
+        Some(_) => Visibility::Inherited,
+        None => Visibility::Hidden,
+    };
+    let Some(report) = report else {
+        return;
+    };
+    if let Some(((), true)) = pointer.pick([((), &*text, global_transform)]) {
+        if let Err(error) = download(&report.file_name(0), &report.to_json()) {
+            error!("{error}");
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::path::PathBuf;
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    ball::{Arena, Ball},
+    bat::{Bat, BatInput, Variant},
+    consts,
+    events::MatchOver,
+    palette::{PaletteSwap, Part},
+    replay::{Recorder, Replay},
+    settings::{GameMode, Settings},
+    state::{GameState, State},
+    text::{self, BitmapText},
+    theme::Themes,
+};
+
+pub const BEST_RUN_FILE: &str = "best-practice.ron";
+const GHOST_ALPHA: f32 = 0.35;
+
+/// Replays a recorded practice run tick by tick, next to the live one.
+pub struct GhostRun {
+    replay: Replay,
+    inputs: Vec<BatInput>,
+    tick: usize,
+    pub bat: Bat,
+    pub ball: Ball,
+    pub score: f32,
+}
+
+impl GhostRun {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            inputs: replay
+                .expand()
+                .into_iter()
+                .map(|(light, _)| light)
+                .collect(),
+            tick: 0,
+            bat: Bat::new(Variant::Light, &replay.tuning),
+            ball: Ball::served(&replay.tuning, &Variant::Dark),
+            score: 0.0,
+            replay,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.inputs.len()
+    }
+
+    /// Runs the same physics as [`crate::bat::update`] and [`crate::ball::update`] for one tick.
+    pub fn step(&mut self) {
+        if self.finished() {
+            return;
+        }
+        let tuning = &self.replay.tuning;
+        let edge = self
+            .replay
+            .edge_at(self.tick)
+            .unwrap_or_else(|| Arena::default().edge);
+        if self.replay.reset_at(self.tick) {
+            self.ball.serve(tuning, &Variant::Dark);
+            self.bat.reset();
+        }
+        self.bat.step(&self.inputs[self.tick], tuning, consts::TICK);
+        let contacts = self.ball.step(
+            [&self.bat],
+            tuning,
+            GameMode::Practice,
+            edge,
+            consts::TICK.as_secs_f32(),
+        );
+        if contacts.hit.is_some() {
+            self.score += self.ball.velocity.x.abs() + self.ball.velocity.y.abs();
+        }
+        if !crate::ball::playable_range(GameMode::Practice).contains(&self.ball.position.y) {
+            self.ball.serve(tuning, &Variant::Dark);
+            self.bat.reset();
+        }
+        self.tick += 1;
+    }
+}
+
+/// Score of a recorded practice run.
+pub fn score(replay: &Replay) -> f32 {
+    let mut run = GhostRun::new(replay.clone());
+    while !run.finished() {
+        run.step();
+    }
+    run.score
+}
+
+pub struct BestRun {
+    pub replay: Replay,
+    pub score: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct Ghost {
+    pub best: Option<BestRun>,
+    pub run: Option<GhostRun>,
+}
+
+#[derive(Component)]
+pub struct GhostBat;
+
+#[derive(Component)]
+pub struct GhostBall;
+
+#[derive(Component)]
+pub struct GapUI;
+
+pub fn practice(settings: Res<Settings>) -> bool {
+    settings.mode == GameMode::Practice
+}
+
+fn best_run_path(settings: &Settings) -> Option<PathBuf> {
+    Some(settings.replay_dir.as_ref()?.join(BEST_RUN_FILE))
+}
+
+pub fn load(settings: Res<Settings>, mut ghost: ResMut<Ghost>) {
+    let Some(path) = best_run_path(&settings) else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+    match Replay::load(&path) {
+        Ok(replay) => {
+            let score = score(&replay);
+            ghost.best = Some(BestRun { replay, score });
+        }
+        Err(error) => warn!("{error}"),
+    }
+}
+
+pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let sprite = Sprite {
+        color: Color::rgba(1.0, 1.0, 1.0, GHOST_ALPHA),
+        ..default()
+    };
+    let bat_texture = asset_server.load("bat_light.png");
+    let ball_texture = asset_server.load("ball.png");
+    commands.spawn((
+        SpriteBundle {
+            texture: bat_texture.clone(),
+            sprite: sprite.clone(),
+            transform: Transform::from_scale(Vec3::splat(1.0 * consts::SCALE)).with_translation(
+                Vec3::new(
+                    0.0,
+                    Variant::Light.default_y_position() * consts::SCALE,
+                    9.0,
+                ),
+            ),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        GhostBat,
+        PaletteSwap::new(Part::Bat(Variant::Light), bat_texture),
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: ball_texture.clone(),
+            sprite,
+            transform: Transform::from_scale(Vec3::splat(1.0 * consts::SCALE))
+                .with_translation(Vec3::new(0.0, 0.0, 14.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        GhostBall,
+        PaletteSwap::new(Part::Ball, ball_texture),
+    ));
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(text::PIXEL))
+                .with_translation(Vec3::new(0.0, 0.0, 200.0)),
+        ),
+        BitmapText::default().with_anchor(Anchor::TopLeft),
+        GapUI,
+    ));
+}
+
+/// Keeps the ghost in step with the live run, starting it over with every new run.
+pub fn advance(mut ghost: ResMut<Ghost>, state: Query<&State>) {
+    let state = state.single();
+    if matches!(state.game_state, GameState::NewGame) || state.match_over {
+        ghost.run = None;
+        return;
+    }
+    if !matches!(state.game_state, GameState::Playing) {
+        return;
+    }
+    if ghost.run.is_none() {
+        ghost.run = ghost
+            .best
+            .as_ref()
+            .map(|best| GhostRun::new(best.replay.clone()));
+    }
+    if let Some(run) = &mut ghost.run {
+        run.step();
+    }
+}
+
+/// Keeps the run just finished as the ghost if it beat the best one.
+pub fn keep_best(
+    settings: Res<Settings>,
+    recorder: Res<Recorder>,
+    mut ghost: ResMut<Ghost>,
+    mut match_over: EventReader<MatchOver>,
+    state: Query<&State>,
+) {
+    let state = state.single();
+    if match_over.is_empty() {
+        return;
+    }
+    match_over.clear();
+    let Some(replay) = &recorder.last else {
+        return;
+    };
+    let score = state.hits_with_velocity;
+    if ghost.best.as_ref().is_some_and(|best| best.score >= score) {
+        return;
+    }
+    if let Some(path) = best_run_path(&settings) {
+        match replay.save(&path) {
+            Ok(()) => info!("new best run of {score}, saved to {}", path.display()),
+            Err(error) => error!("{error}"),
+        }
+    }
+    ghost.best = Some(BestRun {
+        replay: replay.clone(),
+        score,
+    });
+}
+
+pub fn update_sprites(
+    ghost: Res<Ghost>,
+    mut bat: Query<(&mut Transform, &mut Visibility), (With<GhostBat>, Without<GhostBall>)>,
+    mut ball: Query<(&mut Transform, &mut Visibility), (With<GhostBall>, Without<GhostBat>)>,
+) {
+    let (mut bat_transform, mut bat_visibility) = bat.single_mut();
+    let (mut ball_transform, mut ball_visibility) = ball.single_mut();
+    let Some(run) = ghost.run.as_ref().filter(|run| !run.finished()) else {
+        *bat_visibility = Visibility::Hidden;
+        *ball_visibility = Visibility::Hidden;
+        return;
+    };
+    *bat_visibility = Visibility::Inherited;
+    *ball_visibility = Visibility::Inherited;
+    bat_transform.translation.x = run.bat.position_x * consts::SCALE;
+    bat_transform.rotation = run.bat.rotation();
+    ball_transform.translation.x = run.ball.position.x * consts::SCALE;
+    ball_transform.translation.y = run.ball.position.y * consts::SCALE;
+    ball_transform.rotation = run.ball.rotation();
+}
+
+/// Shows how far ahead of or behind the ghost the live run is.
+pub fn update_gap(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    ghost: Res<Ghost>,
+    state: Query<&State>,
+    window: Query<&Window>,
+    mut gap: Query<(&mut BitmapText, &mut Transform), With<GapUI>>,
+) {
+    let state = state.single();
+    let window = window.single();
+    let (mut text, mut transform) = gap.single_mut();
+    let padding = 0.5 * consts::SCALE;
+    transform.translation.x = window.width() * -0.5 + padding;
+    transform.translation.y = window.height() * 0.5 - padding;
+
+    let Some(run) = &ghost.run else {
+        text.set_if_neq(BitmapText::default().with_anchor(Anchor::TopLeft));
+        return;
+    };
+    let gap = state.hits_with_velocity - run.score;
+    let color = themes.current(&settings).readable(if gap < 0.0 {
+        Color::MAROON
+    } else {
+        Color::DARK_GREEN
+    });
+    text.set_if_neq(BitmapText::new(format!("{gap:+.1}"), color).with_anchor(Anchor::TopLeft));
+}
@@ -0,0 +1,163 @@
+use std::{fmt::Write, time::Duration};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    ball::Ball,
+    bat::{Bat, Variant},
+    consts,
+    ghost::Ghost,
+    replay::Recorder,
+    settings::Settings,
+    state::{GameState, Score, State},
+    tuning::GameTuning,
+    GamePlugin,
+};
+
+pub const FRAME_TIME: Duration = consts::TICK;
+
+/// How many updates make `seconds` of play.
+pub fn ticks(seconds: u32) -> u32 {
+    (seconds as f32 / FRAME_TIME.as_secs_f32()).round() as u32
+}
+
+/// Builds the game without a window, renderer or audio output.
+/// Every update advances time by [`FRAME_TIME`], running exactly one fixed tick.
+pub fn app(settings: Settings) -> App {
+    let mut app = App::new();
+    app.insert_resource(settings)
+        .add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            WindowPlugin::default(),
+        ))
+        .add_asset::<TextureAtlas>()
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<Input<GamepadButton>>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+        .add_plugins(GamePlugin);
+    app
+}
+
+pub struct MatchResult {
+    /// `None` when the match was given up at the tick limit.
+    pub winner: Option<Variant>,
+    pub games: Score,
+    pub duration: f32,
+}
+
+/// Lets the cpu play both sides until `matches` matches have been played, giving up on a match
+/// still going after `max_ticks`.
+pub fn run(settings: Settings, matches: usize, max_ticks: u32) -> Vec<MatchResult> {
+    let mut app = app(Settings {
+        autoplay: true,
+        ..settings
+    });
+    let mut results = Vec::new();
+    // like a player pressing start, every menu is shown for at least a frame
+    let mut menu_shown = false;
+    let mut ticks = 0;
+    while results.len() < matches {
+        app.update();
+        ticks += 1;
+        let world = &mut app.world;
+        if ticks > max_ticks {
+            results.push(give_up(world));
+            ticks = 0;
+            menu_shown = false;
+            continue;
+        }
+        let mut state = world.query::<&mut State>().single_mut(world);
+        match &state.game_state {
+            GameState::Countdown { .. } | GameState::Playing => {}
+            GameState::Paused | GameState::NewGame | GameState::Winner(_) if menu_shown => {
+                state.start();
+                menu_shown = false;
+                continue;
+            }
+            GameState::Winner(winner) if state.match_over => {
+                results.push(MatchResult {
+                    winner: Some(winner.clone()),
+                    games: state.games,
+                    duration: state.game_time.elapsed_secs(),
+                });
+                ticks = 0;
+            }
+            GameState::Paused | GameState::NewGame | GameState::Winner(_) => {}
+        }
+        menu_shown = !matches!(
+            state.game_state,
+            GameState::Countdown { .. } | GameState::Playing
+        );
+    }
+    results
+}
+
+/// Ends the match the way restarting it from the pause menu does, keeping the score so far.
+fn give_up(world: &mut World) -> MatchResult {
+    let tuning = world.resource::<GameTuning>().clone();
+    let mut state = world.query::<&mut State>().single_mut(world);
+    let result = MatchResult {
+        winner: None,
+        games: state.games,
+        duration: state.game_time.elapsed_secs(),
+    };
+    state.new_game();
+    let server = state.server.clone();
+    for mut ball in world.query::<&mut Ball>().iter_mut(world) {
+        ball.serve(&tuning, &server);
+    }
+    for mut bat in world.query::<&mut Bat>().iter_mut(world) {
+        bat.reset();
+    }
+    world.resource_mut::<Recorder>().replay = None;
+    world.resource_mut::<Ghost>().run = None;
+    result
+}
+
+pub fn summary(results: &[MatchResult]) -> String {
+    let mut summary = String::new();
+    let mut wins = Score::default();
+    let mut unfinished = 0;
+    for (index, result) in results.iter().enumerate() {
+        let Some(winner) = &result.winner else {
+            unfinished += 1;
+            writeln!(
+                summary,
+                "match {}: unfinished at {}-{} after {:.1}s",
+                index + 1,
+                result.games.light,
+                result.games.dark,
+                result.duration
+            )
+            .expect("writing to a string");
+            continue;
+        };
+        match winner {
+            Variant::Light => wins.light += 1,
+            Variant::Dark => wins.dark += 1,
+        }
+        writeln!(
+            summary,
+            "match {}: {:?} won {}-{} in {:.1}s",
+            index + 1,
+            winner,
+            result.games.get(winner),
+            result.games.get(&winner.opponent()),
+            result.duration
+        )
+        .expect("writing to a string");
+    }
+    let total_time: f32 = results.iter().map(|result| result.duration).sum();
+    write!(summary, "Light {} - {} Dark", wins.light, wins.dark).expect("writing to a string");
+    if unfinished > 0 {
+        write!(summary, ", {unfinished} unfinished").expect("writing to a string");
+    }
+    writeln!(
+        summary,
+        ", average match {:.1}s",
+        total_time / results.len().max(1) as f32
+    )
+    .expect("writing to a string");
+    summary
+}
@@ -0,0 +1,87 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    ball::{Ball, HEAT_THRESHOLDS},
+    consts,
+    state::{GameState, State},
+};
+
+const METER_WIDTH: f32 = 12.0 * consts::SCALE;
+const METER_HEIGHT: f32 = 0.5 * consts::SCALE;
+/// Below the score at the top of the window.
+const METER_TOP: f32 = 5.0 * consts::SCALE;
+
+#[derive(Component)]
+pub struct HeatMeter;
+
+#[derive(Component)]
+pub struct HeatMeterFill;
+
+/// White when the rally is cold, glowing orange once it's at full heat.
+pub fn heat_color(heat: f32) -> Color {
+    Color::rgb(1.0, 1.0 - 0.6 * heat, 1.0 - 0.9 * heat)
+}
+
+pub fn spawn(mut commands: Commands) {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.0, 0.0, 0.0, 0.25),
+                    custom_size: Some(Vec2::new(METER_WIDTH, METER_HEIGHT)),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            HeatMeter,
+        ))
+        .with_children(|meter| {
+            meter.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(0.0, METER_HEIGHT)),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec3::new(METER_WIDTH * -0.5, 0.0, 1.0)),
+                    ..default()
+                },
+                HeatMeterFill,
+            ));
+        });
+}
+
+/// Fills the meter with the heat of the rally in play.
+pub fn update_meter(
+    state: Query<&State>,
+    ball: Query<&Ball>,
+    window: Query<&Window>,
+    mut meter: Query<(&mut Transform, &mut Visibility), With<HeatMeter>>,
+    mut fill: Query<&mut Sprite, With<HeatMeterFill>>,
+) {
+    let state = state.single();
+    let ball = ball.single();
+    let window = window.single();
+    let (mut transform, mut visibility) = meter.single_mut();
+    let mut fill = fill.single_mut();
+
+    transform.translation.y = window.height() * 0.5 - METER_TOP;
+    *visibility = match state.game_state {
+        GameState::Countdown { .. } | GameState::Playing | GameState::Paused => {
+            Visibility::Inherited
+        }
+        GameState::NewGame | GameState::Winner(_) => Visibility::Hidden,
+    };
+    fill.custom_size = Some(Vec2::new(METER_WIDTH * ball.heat, METER_HEIGHT));
+    // the fill changes color as thresholds are passed, so unlocked smashes stand out
+    let level = ball.heat_level() as f32 / HEAT_THRESHOLDS.len() as f32;
+    fill.color = heat_color(0.25 + 0.75 * level);
+}
+
+pub fn tint_ball(mut ball: Query<(&Ball, &mut Sprite)>) {
+    for (ball, mut sprite) in &mut ball {
+        sprite.color = heat_color(ball.heat);
+    }
+}
@@ -0,0 +1,307 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use bevy::{prelude::*, sprite::Anchor};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    consts,
+    events::{MatchOver, MenuConfirmed, MenuMoved},
+    instant_replay::InstantReplay,
+    keymap,
+    settings::{Difficulty, GameMode, Settings},
+    state::{GameState, State},
+    storage,
+    text::{self, BitmapText},
+    theme::Themes,
+    title::Screen,
+};
+
+pub const TABLE_SIZE: usize = 10;
+pub const INITIALS: usize = 3;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub initials: String,
+    pub score: u32,
+    pub time: f32,
+}
+
+/// The best scores for every mode and difficulty, best first.
+#[derive(Resource, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct HighScores {
+    tables: BTreeMap<(GameMode, Difficulty), Vec<Entry>>,
+}
+
+impl HighScores {
+    pub fn table(&self, mode: GameMode, difficulty: Difficulty) -> &[Entry] {
+        self.tables
+            .get(&(mode, difficulty))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn qualifies(&self, mode: GameMode, difficulty: Difficulty, score: u32) -> bool {
+        let table = self.table(mode, difficulty);
+        score > 0
+            && (table.len() < TABLE_SIZE || table.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds an entry below any equal scores, returning its place if it made the table.
+    pub fn insert(
+        &mut self,
+        mode: GameMode,
+        difficulty: Difficulty,
+        entry: Entry,
+    ) -> Option<usize> {
+        let table = self.tables.entry((mode, difficulty)).or_default();
+        let place = table.partition_point(|other| other.score >= entry.score);
+        if place >= TABLE_SIZE {
+            return None;
+        }
+        table.insert(place, entry);
+        table.truncate(TABLE_SIZE);
+        Some(place)
+    }
+}
+
+/// Arcade style initials entry after a match that made the table.
+#[derive(Resource)]
+pub struct InitialsEntry {
+    pub score: u32,
+    pub time: f32,
+    pub letters: [char; INITIALS],
+    pub cursor: usize,
+}
+
+impl InitialsEntry {
+    fn new(score: u32, time: f32) -> Self {
+        Self {
+            score,
+            time,
+            letters: ['A'; INITIALS],
+            cursor: 0,
+        }
+    }
+
+    fn cycle(&mut self, by: i8) {
+        let letter = &mut self.letters[self.cursor];
+        let index = (*letter as u8 - b'A') as i8;
+        *letter = (b'A' + (index + by).rem_euclid(26) as u8) as char;
+    }
+
+    fn type_letter(&mut self, letter: char) {
+        self.letters[self.cursor] = letter.to_ascii_uppercase();
+        self.cursor = (self.cursor + 1).min(INITIALS - 1);
+    }
+}
+
+#[derive(Component)]
+pub struct HighScoreUI;
+
+#[derive(Component)]
+pub struct InitialsUI;
+
+pub fn load(settings: Res<Settings>, mut scores: ResMut<HighScores>) {
+    let Some(path) = &settings.high_scores else {
+        return;
+    };
+    match storage::load_ron(path) {
+        Ok(loaded) => *scores = loaded,
+        Err(error) => warn!("{error}"),
+    }
+}
+
+pub fn spawn(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(text::SMALL_PIXEL))
+                .with_translation(Vec3::new(0.0, 0.0, 200.0)),
+        ),
+        table_bitmap(String::new(), text::INK),
+        HighScoreUI,
+    ));
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(text::PIXEL)).with_translation(Vec3::new(
+                0.0,
+                -17.0 * consts::SCALE,
+                200.0,
+            )),
+        ),
+        initials_bitmap(String::new(), text::INK),
+        InitialsUI,
+    ));
+}
+
+/// Asks for initials once a match played by a person ends with a score that makes the table.
+pub fn start_entry(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    scores: Res<HighScores>,
+    mut match_over: EventReader<MatchOver>,
+    state: Query<&State>,
+) {
+    let state = state.single();
+    if match_over.is_empty() {
+        return;
+    }
+    match_over.clear();
+    if !settings.has_human() {
+        return;
+    }
+    let score = state.hits_with_velocity as u32;
+    if scores.qualifies(settings.mode, settings.difficulty, score) {
+        commands.insert_resource(InitialsEntry::new(score, state.game_time.elapsed_secs()));
+    }
+}
+
+/// Takes every key while entering initials, so typing `P` or `R` doesn't start or restart.
+#[allow(clippy::too_many_arguments)]
+pub fn enter_initials(
+    mut commands: Commands,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    settings: Res<Settings>,
+    mut entry: ResMut<InitialsEntry>,
+    mut scores: ResMut<HighScores>,
+    state: Query<&State>,
+    mut moved: EventWriter<MenuMoved>,
+    mut confirmed: EventWriter<MenuConfirmed>,
+) {
+    let state = state.single();
+    if !state.match_over || !matches!(state.game_state, GameState::Winner(_)) {
+        commands.remove_resource::<InitialsEntry>();
+        return;
+    }
+    if entry.is_added() {
+        // the key that skipped the instant replay isn't a letter
+        characters.clear();
+    }
+    for event in characters.iter() {
+        if event.char.is_ascii_alphabetic() {
+            entry.type_letter(event.char);
+            moved.send(MenuMoved);
+        }
+    }
+    let navigation = [
+        keymap::letter_up(),
+        keymap::letter_down(),
+        keymap::cursor_left(),
+        keymap::cursor_right(),
+        KeyCode::Back,
+    ];
+    if keys.any_just_pressed(navigation) {
+        moved.send(MenuMoved);
+    }
+    if keys.just_pressed(keymap::letter_up()) {
+        entry.cycle(1);
+    }
+    if keys.just_pressed(keymap::letter_down()) {
+        entry.cycle(-1);
+    }
+    if keys.just_pressed(keymap::cursor_left()) || keys.just_pressed(KeyCode::Back) {
+        entry.cursor = entry.cursor.saturating_sub(1);
+    }
+    if keys.just_pressed(keymap::cursor_right()) {
+        entry.cursor = (entry.cursor + 1).min(INITIALS - 1);
+    }
+    if keys.just_pressed(keymap::confirm()) {
+        let new = Entry {
+            initials: entry.letters.iter().collect(),
+            score: entry.score,
+            time: entry.time,
+        };
+        scores.insert(settings.mode, settings.difficulty, new);
+        if let Some(path) = &settings.high_scores {
+            if let Err(error) = storage::save_ron(path, &*scores) {
+                error!("{error}");
+            }
+        }
+        commands.remove_resource::<InitialsEntry>();
+        confirmed.send(MenuConfirmed);
+    }
+    keys.reset_all();
+}
+
+/// The table for `mode` and `difficulty` as shown on screen.
+pub fn table_text(scores: &HighScores, mode: GameMode, difficulty: Difficulty) -> String {
+    let table = scores.table(mode, difficulty);
+    let mut text = "HIGH SCORES".to_string();
+    if table.is_empty() {
+        text.push_str("\nno scores yet");
+    }
+    for (place, entry) in table.iter().enumerate() {
+        write!(
+            text,
+            "\n{:>2} {} {:>7} {:>6.1}s",
+            place + 1,
+            entry.initials,
+            entry.score,
+            entry.time
+        )
+        .expect("writing to a string");
+    }
+    text
+}
+
+fn table_bitmap(value: String, ink: Color) -> BitmapText {
+    BitmapText::new(value, ink).with_anchor(Anchor::CenterRight)
+}
+
+fn initials_bitmap(value: String, ink: Color) -> BitmapText {
+    BitmapText::new(value, ink)
+        .with_alignment(TextAlignment::Center)
+        .with_anchor(Anchor::TopCenter)
+}
+
+/// Shows the table on the start screen, the title screen shows it on its own page.
+#[allow(clippy::too_many_arguments)]
+pub fn update_ui(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    scores: Res<HighScores>,
+    screen: Option<Res<Screen>>,
+    entry: Option<Res<InitialsEntry>>,
+    instant_replay: Option<Res<InstantReplay>>,
+    state: Query<&State>,
+    window: Query<&Window>,
+    mut table_ui: Query<
+        (&mut BitmapText, &mut Transform),
+        (With<HighScoreUI>, Without<InitialsUI>),
+    >,
+    mut initials_ui: Query<&mut BitmapText, (With<InitialsUI>, Without<HighScoreUI>)>,
+) {
+    let state = state.single();
+    let window = window.single();
+    let ink = themes.current(&settings).ink;
+    let (mut table, mut transform) = table_ui.single_mut();
+    transform.translation.x = window.width() * 0.5 - consts::SCALE;
+
+    let mut value = String::new();
+    if matches!(state.game_state, GameState::NewGame) && screen.is_none() {
+        value = table_text(&scores, settings.mode, settings.difficulty);
+    }
+    table.set_if_neq(table_bitmap(value, ink));
+
+    let value = match entry.filter(|_| instant_replay.is_none()) {
+        Some(entry) => {
+            let letters: Vec<String> = entry
+                .letters
+                .iter()
+                .enumerate()
+                .map(|(index, letter)| {
+                    if index == entry.cursor {
+                        format!("[{letter}]")
+                    } else {
+                        format!(" {letter} ")
+                    }
+                })
+                .collect();
+            format!("NEW HIGH SCORE {}\n{}", entry.score, letters.concat())
+        }
+        None => String::new(),
+    };
+    initials_ui
+        .single_mut()
+        .set_if_neq(initials_bitmap(value, ink));
+}
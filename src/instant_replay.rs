@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    ball::Ball,
+    bat::Bat,
+    consts, keymap,
+    state::{GameState, State},
+};
+
+/// Seconds of play kept around to show after a point.
+const BUFFER_SECONDS: f32 = 2.0;
+const SLOW_MOTION: f32 = 0.35;
+const ZOOM: f32 = 0.6;
+const CAMERA_FOLLOW: f32 = 6.0;
+
+/// Where the ball and bats were drawn during one tick.
+#[derive(Clone)]
+pub struct Snapshot(Vec<(Entity, Transform)>);
+
+/// The last [`BUFFER_SECONDS`] of the current rally.
+#[derive(Resource, Default)]
+pub struct RallyBuffer(VecDeque<Snapshot>);
+
+impl RallyBuffer {
+    fn capacity() -> usize {
+        (BUFFER_SECONDS / consts::TICK.as_secs_f32()) as usize
+    }
+
+    fn push(&mut self, snapshot: Snapshot) {
+        if self.0.len() >= Self::capacity() {
+            self.0.pop_front();
+        }
+        self.0.push_back(snapshot);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Shown on the winner screen until it has played through or is skipped.
+#[derive(Resource)]
+pub struct InstantReplay {
+    frames: Vec<Snapshot>,
+    elapsed: f32,
+}
+
+impl InstantReplay {
+    pub fn frame(&self) -> usize {
+        (self.elapsed / consts::TICK.as_secs_f32()) as usize
+    }
+
+    pub fn finished(&self) -> bool {
+        self.frame() >= self.frames.len()
+    }
+}
+
+/// Keeps the rally in the buffer and starts the instant replay once a point is scored.
+pub fn capture(
+    mut commands: Commands,
+    mut buffer: ResMut<RallyBuffer>,
+    state: Query<&State>,
+    objects: Query<(Entity, &Transform), Or<(With<Ball>, With<Bat>)>>,
+) {
+    let state = state.single();
+    let snapshot = || {
+        Snapshot(
+            objects
+                .iter()
+                .map(|(entity, transform)| (entity, *transform))
+                .collect(),
+        )
+    };
+    match state.game_state {
+        GameState::Playing => buffer.push(snapshot()),
+        GameState::Winner(_) if !buffer.0.is_empty() => {
+            buffer.push(snapshot());
+            commands.insert_resource(InstantReplay {
+                frames: buffer.0.drain(..).collect(),
+                elapsed: 0.0,
+            });
+        }
+        GameState::NewGame => buffer.clear(),
+        GameState::Winner(_) | GameState::Paused | GameState::Countdown { .. } => {}
+    }
+}
+
+/// Plays the instant replay in slow motion with the camera following the ball.
+/// The pause key skips it, so it has to run before [`crate::state::update`].
+pub fn play(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut replay: ResMut<InstantReplay>,
+    mut state: Query<(&State, &mut Visibility)>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+    mut objects: Query<
+        (&mut Transform, Option<&Ball>, Option<&Bat>),
+        (Or<(With<Ball>, With<Bat>)>, Without<Camera2d>),
+    >,
+) {
+    let (state, mut visibility) = state.single_mut();
+    let (mut camera_transform, mut projection) = camera.single_mut();
+    let skipped = keys.clear_just_pressed(keymap::pause());
+    replay.elapsed += time.delta_seconds() * SLOW_MOTION;
+
+    if skipped || replay.finished() || !matches!(state.game_state, GameState::Winner(_)) {
+        commands.remove_resource::<InstantReplay>();
+        *visibility = Visibility::Inherited;
+        camera_transform.translation = Vec3::new(0.0, 0.0, camera_transform.translation.z);
+        projection.scale = 1.0;
+        // show everything where it is now rather than where the replay left it
+        for (mut transform, ball, bat) in &mut objects {
+            if let Some(ball) = ball {
+                transform.translation.x = ball.position.x * consts::SCALE;
+                transform.translation.y = ball.position.y * consts::SCALE;
+            }
+            if let Some(bat) = bat {
+                transform.translation.x = bat.position_x * consts::SCALE;
+                transform.rotation = bat.rotation();
+            }
+        }
+        return;
+    }
+
+    *visibility = Visibility::Hidden;
+    let Snapshot(frame) = &replay.frames[replay.frame()];
+    for (entity, recorded) in frame {
+        let Ok((mut transform, ball, _)) = objects.get_mut(*entity) else {
+            continue;
+        };
+        *transform = *recorded;
+        if ball.is_some() {
+            let follow = 1.0 - (-CAMERA_FOLLOW * time.delta_seconds()).exp();
+            let target = recorded.translation.truncate();
+            let position = camera_transform.translation.truncate().lerp(target, follow);
+            camera_transform.translation = position.extend(camera_transform.translation.z);
+            projection.scale += (ZOOM - projection.scale) * follow;
+        }
+    }
+}
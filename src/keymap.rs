@@ -1,4 +1,4 @@
-use bevy::prelude::KeyCode;
+use bevy::prelude::{GamepadButton, GamepadButtonType, Input, KeyCode};
 
 use crate::bat::Variant;
 
@@ -37,3 +37,74 @@ pub fn pause() -> KeyCode {
 pub fn restart() -> KeyCode {
     KeyCode::R
 }
+
+pub fn debug_overlay() -> KeyCode {
+    KeyCode::F3
+}
+
+pub fn console() -> KeyCode {
+    KeyCode::Grave
+}
+
+pub fn playback_pause() -> KeyCode {
+    KeyCode::Space
+}
+
+pub fn playback_faster() -> KeyCode {
+    KeyCode::Up
+}
+
+pub fn playback_slower() -> KeyCode {
+    KeyCode::Down
+}
+
+pub fn playback_step() -> KeyCode {
+    KeyCode::Right
+}
+
+pub fn letter_up() -> KeyCode {
+    KeyCode::Up
+}
+
+pub fn letter_down() -> KeyCode {
+    KeyCode::Down
+}
+
+pub fn cursor_left() -> KeyCode {
+    KeyCode::Left
+}
+
+pub fn cursor_right() -> KeyCode {
+    KeyCode::Right
+}
+
+pub fn confirm() -> KeyCode {
+    KeyCode::Return
+}
+
+pub fn mute() -> KeyCode {
+    KeyCode::M
+}
+
+pub fn audio_menu() -> KeyCode {
+    KeyCode::O
+}
+
+pub fn menu_close() -> KeyCode {
+    KeyCode::Escape
+}
+
+pub fn menu_up() -> KeyCode {
+    KeyCode::Up
+}
+
+pub fn menu_down() -> KeyCode {
+    KeyCode::Down
+}
+
+/// Whether any gamepad just pressed a button of this type.
+pub fn gamepad_pressed(buttons: &Input<GamepadButton>, button_type: GamepadButtonType) -> bool {
+    buttons
+        .get_just_pressed()
+        .any(|button| button.button_type == button_type)
+}
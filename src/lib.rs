@@ -0,0 +1,282 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::{input::InputSystem, prelude::*};
+
+pub mod ai;
+pub mod announce;
+pub mod audio;
+pub mod audio_settings;
+pub mod ball;
+pub mod bat;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+pub mod consts;
+pub mod countdown;
+pub mod debug;
+#[cfg(feature = "dev")]
+pub mod dev;
+pub mod events;
+pub mod export;
+pub mod ghost;
+pub mod headless;
+pub mod heat;
+pub mod highscores;
+pub mod instant_replay;
+pub mod keymap;
+pub mod palette;
+pub mod pause_menu;
+pub mod players;
+pub mod replay;
+pub mod settings;
+pub mod state;
+pub mod stats;
+pub mod storage;
+pub mod table;
+pub mod text;
+pub mod theme;
+pub mod title;
+pub mod tournament;
+pub mod tuning;
+pub mod ui;
+
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app
+            .world
+            .get_resource_or_insert_with(settings::Settings::default)
+            .clone();
+        let tuning_errors = tuning::TuningErrors::default();
+        app.insert_resource(settings::GameRng::new(settings.seed))
+            .insert_resource(FixedTime::new(consts::TICK))
+            .init_resource::<ball::Arena>()
+            .init_resource::<audio::MusicMix>()
+            .init_resource::<audio::EffectRng>()
+            .init_resource::<audio_settings::AudioSettings>()
+            .init_resource::<players::PlayerSettings>()
+            .init_resource::<replay::Recorder>()
+            .init_resource::<instant_replay::RallyBuffer>()
+            .init_resource::<ghost::Ghost>()
+            .init_resource::<highscores::HighScores>()
+            .init_resource::<stats::MatchStats>()
+            .init_resource::<export::Reports>()
+            .init_resource::<announce::Announcements>()
+            .add_event::<events::BallHit>()
+            .add_event::<events::WallBounce>()
+            .add_event::<events::Served>()
+            .add_event::<events::PointScored>()
+            .add_event::<events::MatchOver>()
+            .add_event::<events::PointStreak>()
+            .add_event::<events::FastestShot>()
+            .add_event::<events::CountdownTick>()
+            .add_event::<events::Paused>()
+            .add_event::<events::Resumed>()
+            .add_event::<events::MenuMoved>()
+            .add_event::<events::MenuConfirmed>()
+            .add_asset::<tuning::GameTuning>()
+            .add_asset_loader(tuning::GameTuningLoader::new(tuning_errors.clone()))
+            .insert_resource(tuning_errors)
+            .init_resource::<tuning::GameTuning>()
+            .add_event::<tuning::TuningLoadFailed>()
+            .add_systems(Startup, tuning::spawn)
+            .add_asset::<theme::ThemeManifest>()
+            .add_asset_loader(theme::ThemeManifestLoader)
+            .init_resource::<theme::Themes>()
+            .add_systems(Startup, theme::spawn)
+            .add_systems(Startup, setup)
+            .add_systems(Startup, table::spawn)
+            .add_systems(Startup, ball::spawn)
+            .add_systems(Startup, state::spawn)
+            .add_systems(Startup, audio::spawn_music)
+            .add_systems(Startup, (audio_settings::load, audio_settings::spawn))
+            .add_systems(Startup, players::load)
+            .add_systems(Startup, (text::load, ui::spawn))
+            .add_systems(Startup, (highscores::load, highscores::spawn))
+            .add_systems(Startup, stats::spawn)
+            .add_systems(Startup, heat::spawn)
+            .add_systems(Startup, pause_menu::spawn)
+            .add_systems(Startup, announce::spawn)
+            .add_systems(Startup, countdown::spawn)
+            .add_systems(Startup, title::spawn)
+            .add_systems(Startup, tournament::spawn)
+            .add_systems(Startup, (ghost::load, ghost::spawn))
+            .add_systems(Startup, replay::spawn_hud)
+            .add_systems(PreUpdate, bat::read_input.after(InputSystem))
+            .add_systems(
+                FixedUpdate,
+                (
+                    replay::begin.run_if(not(resource_exists::<replay::Playback>())),
+                    ai::update,
+                    replay::play.run_if(resource_exists::<replay::Playback>()),
+                    replay::record.run_if(not(resource_exists::<replay::Playback>())),
+                    bat::update,
+                    stats::track_bats,
+                    ball::update,
+                    state::score,
+                    stats::collect,
+                    announce::queue,
+                    export::export,
+                    instant_replay::capture.run_if(not(resource_exists::<replay::Playback>())),
+                    ghost::advance
+                        .run_if(ghost::practice)
+                        .run_if(not(resource_exists::<replay::Playback>())),
+                    state::tick,
+                    replay::finish.run_if(not(resource_exists::<replay::Playback>())),
+                    ghost::keep_best
+                        .run_if(ghost::practice)
+                        .run_if(not(resource_exists::<replay::Playback>())),
+                )
+                    .chain(),
+            )
+            .add_systems(Update, tuning::update)
+            .add_systems(
+                Update,
+                (
+                    theme::update,
+                    theme::apply_background,
+                    table::apply_theme,
+                    theme::update_particles,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                ball::update_arena.run_if(not(resource_exists::<replay::Playback>())),
+            )
+            .add_systems(
+                Update,
+                (replay::controls, replay::update_hud)
+                    .run_if(resource_exists::<replay::Playback>()),
+            )
+            .add_systems(
+                Update,
+                (ghost::update_sprites, ghost::update_gap).run_if(ghost::practice),
+            )
+            .add_systems(
+                Update,
+                (
+                    ui::update,
+                    ui::update_scoreboard,
+                    announce::show,
+                    text::render,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, (heat::update_meter, heat::tint_ball))
+            .add_systems(
+                Update,
+                palette::swap.run_if(resource_exists::<Assets<Image>>()),
+            )
+            .add_systems(
+                Update,
+                (
+                    audio::play_effects,
+                    audio::update_effects,
+                    audio::spawn_stems,
+                    audio::update_music,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    audio_settings::toggle_mute
+                        .run_if(not(resource_exists::<audio_settings::AudioMenu>()))
+                        .run_if(not(resource_exists::<highscores::InitialsEntry>()))
+                        .run_if(not(title::typing)),
+                    audio_settings::open_menu.run_if(not(title::typing)),
+                    audio_settings::menu.run_if(resource_exists::<audio_settings::AudioMenu>()),
+                    audio_settings::update_ui,
+                )
+                    .chain()
+                    .before(state::update),
+            )
+            .add_systems(
+                Update,
+                instant_replay::play
+                    .run_if(resource_exists::<instant_replay::InstantReplay>())
+                    .before(state::update),
+            )
+            .add_systems(
+                Update,
+                (
+                    highscores::start_entry,
+                    highscores::enter_initials
+                        .run_if(resource_exists::<highscores::InitialsEntry>())
+                        .run_if(not(resource_exists::<instant_replay::InstantReplay>()))
+                        .after(instant_replay::play),
+                )
+                    .chain()
+                    .before(state::update),
+            )
+            .add_systems(
+                Update,
+                (
+                    pause_menu::open,
+                    pause_menu::navigate
+                        .pipe(pause_menu::choose)
+                        .run_if(resource_exists::<pause_menu::PauseMenu>())
+                        .run_if(not(resource_exists::<audio_settings::AudioMenu>())),
+                    pause_menu::update_ui,
+                )
+                    .chain()
+                    .after(audio_settings::menu)
+                    .before(state::update),
+            )
+            .add_systems(
+                Update,
+                (
+                    title::show,
+                    title::navigate
+                        .pipe(title::choose)
+                        .run_if(resource_exists::<title::Screen>())
+                        .run_if(not(resource_exists::<audio_settings::AudioMenu>())),
+                    title::update_ui,
+                )
+                    .chain()
+                    .after(audio_settings::menu)
+                    .before(state::update),
+            )
+            .add_systems(
+                Update,
+                title::leave_replay
+                    .run_if(resource_exists::<replay::Playback>())
+                    .after(replay::update_hud),
+            )
+            .add_systems(Update, highscores::update_ui.after(state::update))
+            .add_systems(Update, stats::update_ui.after(state::update))
+            .add_systems(Update, countdown::update.after(state::update))
+            .add_systems(Update, (state::update, state::update_message).chain())
+            .add_systems(
+                Update,
+                (
+                    tournament::update.run_if(resource_exists::<tournament::Tournament>()),
+                    tournament::update_ui,
+                )
+                    .chain()
+                    .after(state::update)
+                    .before(state::update_message),
+            )
+            .add_systems(Update, ui::window_resized);
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(Startup, export::spawn_download_button)
+            .add_systems(Update, export::download_button);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tuning: Res<tuning::GameTuning>,
+    settings: Res<settings::Settings>,
+    playback: Option<Res<replay::Playback>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+    bat::spawn_players(
+        &mut commands,
+        &asset_server,
+        &tuning,
+        &settings,
+        playback.is_some(),
+    );
+}
@@ -1,46 +1,83 @@
 use bevy::prelude::*;
-
-mod audio;
-mod ball;
-mod bat;
-mod consts;
-mod keymap;
-mod state;
-mod table;
-mod ui;
+use bordtennis::{replay::Playback, GamePlugin};
 
 fn main() {
-    App::new()
-        .insert_resource(ClearColor(Color::hex("bdadf7").unwrap()))
+    #[cfg(not(target_arch = "wasm32"))]
+    let (settings, window, playback) = {
+        use bordtennis::{cli::Cli, headless, replay::Replay, settings::Settings};
+        use clap::Parser;
+
+        let cli = Cli::parse();
+        if let Some(matches) = cli.headless {
+            let max_ticks = headless::ticks(cli.max_match_seconds);
+            let results = headless::run(cli.settings(), matches, max_ticks);
+            print!("{}", headless::summary(&results));
+            return;
+        }
+        match &cli.replay {
+            Some(path) => {
+                let replay = match Replay::load(path) {
+                    Ok(replay) => replay,
+                    Err(error) => {
+                        eprintln!("{error}");
+                        std::process::exit(1);
+                    }
+                };
+                let settings = Settings {
+                    muted: cli.mute,
+                    audio: Some(cli.audio_settings.clone()),
+                    players: Some(cli.player_settings.clone()),
+                    ..replay.settings()
+                };
+                (settings, cli.window(), Some(Playback::new(replay)))
+            }
+            None => (cli.settings(), cli.window(), None),
+        }
+    };
+    #[cfg(target_arch = "wasm32")]
+    let (settings, window, playback) = (
+        bordtennis::settings::Settings {
+            high_scores: Some("highscores.ron".into()),
+            audio: Some("audio.ron".into()),
+            players: Some("players.ron".into()),
+            ..default()
+        },
+        Window {
+            title: "bordtennis".into(),
+            fit_canvas_to_parent: true,
+            ..default()
+        },
+        None::<Playback>,
+    );
+    // the `Settings` defaults leave the title screen and countdown off for headless runs and
+    // tests, the windowed game turns them on here
+    let settings = bordtennis::settings::Settings {
+        title_screen: true,
+        countdown: true,
+        ..settings
+    };
+
+    let mut app = App::new();
+    app.insert_resource(ClearColor(bordtennis::palette::background()))
+        .insert_resource(settings)
         .add_plugins(
             DefaultPlugins
                 .set(ImagePlugin::default_nearest())
                 .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "bordtennis".into(),
-                        fit_canvas_to_parent: true,
-                        ..default()
-                    }),
+                    primary_window: Some(window),
+                    ..default()
+                })
+                .set(AssetPlugin {
+                    #[cfg(feature = "dev")]
+                    watch_for_changes: bordtennis::dev::change_watcher(),
                     ..default()
                 }),
-        )
-        .add_systems(Startup, setup)
-        .add_systems(Startup, table::spawn)
-        .add_systems(Startup, ball::spawn)
-        .add_systems(Startup, state::spawn)
-        .add_systems(Startup, audio::spawn_music)
-        .add_systems(Startup, ui::spawn)
-        .add_systems(Update, bat::update)
-        .add_systems(Update, ui::update)
-        .add_systems(Update, ball::update)
-        .add_systems(Update, state::update)
-        .add_systems(Update, ui::window_resized)
-        .run();
-}
-
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(Camera2dBundle::default());
-    #[cfg(not(feature = "singleplayer"))]
-    bat::spawn(&mut commands, &asset_server, bat::Variant::Dark);
-    bat::spawn(&mut commands, &asset_server, bat::Variant::Light);
+        );
+    if let Some(playback) = playback {
+        app.insert_resource(playback);
+    }
+    app.add_plugins(GamePlugin);
+    #[cfg(feature = "dev")]
+    app.add_plugins(bordtennis::dev::DevPlugin);
+    app.run();
 }
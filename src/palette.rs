@@ -0,0 +1,175 @@
+//! The color palettes players can pick for their bat and the ball, swapped into the sprites as
+//! they're loaded.
+
+use std::sync::OnceLock;
+
+use bevy::{asset::HandleId, prelude::*, render::render_resource::TextureFormat, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{bat::Variant, players::PlayerSettings};
+
+const SOURCE: &str = include_str!("../assets/palettes.ron");
+
+#[derive(Deserialize)]
+struct PaletteFile {
+    background: String,
+    palettes: Vec<PaletteEntry>,
+}
+
+#[derive(Deserialize)]
+struct PaletteEntry {
+    name: String,
+    colors: (String, String, String),
+}
+
+/// A light, mid and dark color, in the places of the ones the sprites are drawn with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub name: String,
+    pub colors: [Color; 3],
+}
+
+struct Palettes {
+    background: Color,
+    palettes: Vec<Palette>,
+}
+
+fn color(hex: &str) -> Color {
+    Color::hex(hex).unwrap_or_else(|error| panic!("palettes.ron has a bad color {hex}: {error:?}"))
+}
+
+fn palettes() -> &'static Palettes {
+    static PALETTES: OnceLock<Palettes> = OnceLock::new();
+    PALETTES.get_or_init(|| {
+        let file: PaletteFile = ron::from_str(SOURCE).expect("palettes.ron should parse");
+        assert!(!file.palettes.is_empty(), "palettes.ron has no palettes");
+        Palettes {
+            background: color(&file.background),
+            palettes: file
+                .palettes
+                .into_iter()
+                .map(|entry| Palette {
+                    name: entry.name,
+                    colors: [
+                        color(&entry.colors.0),
+                        color(&entry.colors.1),
+                        color(&entry.colors.2),
+                    ],
+                })
+                .collect(),
+        }
+    })
+}
+
+/// Every palette, the first one is what the sprites are drawn with.
+pub fn all() -> &'static [Palette] {
+    &palettes().palettes
+}
+
+/// `index`, or the first palette's if there's no such palette, like in an old save file.
+fn valid(index: usize) -> usize {
+    if index < all().len() {
+        index
+    } else {
+        0
+    }
+}
+
+/// The palette at `index`, the first one if there's no such palette.
+pub fn get(index: usize) -> &'static Palette {
+    &all()[valid(index)]
+}
+
+/// The color behind the table.
+pub fn background() -> Color {
+    palettes().background
+}
+
+/// A copy of `image` with every pixel in a color of `from` changed to the color in its place in
+/// `to`. Only 8 bit rgba images are changed, like the ones loaded from the sprites.
+pub fn recolor(image: &Image, from: &Palette, to: &Palette) -> Image {
+    let mut recolored = image.clone();
+    if !matches!(
+        image.texture_descriptor.format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    ) {
+        warn!(
+            "can't swap the palette of a {:?} image",
+            image.texture_descriptor.format
+        );
+        return recolored;
+    }
+    let from = from.colors.map(|color| color.as_rgba_u8());
+    let to = to.colors.map(|color| color.as_rgba_u8());
+    for pixel in recolored.data.chunks_exact_mut(4) {
+        if pixel[3] == 0 {
+            continue;
+        }
+        if let Some(index) = from.iter().position(|color| color[..3] == pixel[..3]) {
+            pixel[..3].copy_from_slice(&to[index][..3]);
+        }
+    }
+    recolored
+}
+
+/// Which choice of [`PlayerSettings`] a sprite is drawn in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Part {
+    Bat(Variant),
+    Ball,
+}
+
+/// Draws a sprite loaded from `source` in the palette picked for its part.
+#[derive(Component, Clone, Debug)]
+pub struct PaletteSwap {
+    pub part: Part,
+    pub source: Handle<Image>,
+}
+
+impl PaletteSwap {
+    pub fn new(part: Part, source: Handle<Image>) -> Self {
+        Self { part, source }
+    }
+}
+
+/// Points every swapped sprite at its image in the picked palette, recoloring it the first time
+/// it's needed and again after its image is reloaded. Sprites stay in the palette they were
+/// drawn with until their image is loaded.
+pub fn swap(
+    players: Res<PlayerSettings>,
+    mut events: EventReader<AssetEvent<Image>>,
+    mut images: ResMut<Assets<Image>>,
+    mut recolored: Local<HashMap<(HandleId, usize), Handle<Image>>>,
+    mut sprites: Query<(&PaletteSwap, &mut Handle<Image>)>,
+) {
+    for event in events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            recolored.retain(|(source, _), recolor| {
+                let stale = *source == handle.id();
+                if stale {
+                    images.remove(&*recolor);
+                }
+                !stale
+            });
+        }
+    }
+    for (swap, mut texture) in &mut sprites {
+        let palette = valid(players.palette(&swap.part));
+        let wanted = if palette == 0 {
+            swap.source.clone()
+        } else if let Some(handle) = recolored.get(&(swap.source.id(), palette)) {
+            handle.clone()
+        } else {
+            let Some(image) = images.get(&swap.source) else {
+                continue;
+            };
+            let image = recolor(image, &all()[0], get(palette));
+            let handle = images.add(image);
+            recolored.insert((swap.source.id(), palette), handle.clone());
+            handle
+        };
+        if *texture != wanted {
+            *texture = wanted;
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    audio_settings::AudioMenu,
+    ball::Ball,
+    bat::Bat,
+    events::{MenuConfirmed, MenuMoved, Resumed, Served},
+    ghost::Ghost,
+    instant_replay::RallyBuffer,
+    keymap,
+    replay::Recorder,
+    settings::Settings,
+    state::{self, GameState, State},
+    text::{self, BitmapText, Pointer},
+    theme::Themes,
+    tuning::GameTuning,
+};
+
+/// Space from one menu entry to the next, a line of text and a font pixel.
+const ITEM_SPACING: f32 = (text::LINE_HEIGHT + 1) as f32 * text::PIXEL;
+/// Menu entries other than the selected one are drawn in faded ink.
+const UNSELECTED_ALPHA: f32 = 0.5;
+const CONTROLS: &str = "light: A D move, W swing, left shift run
+dark: arrows move, up swings, right shift run
+P pause, R restart, M mute, O volume";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseAction {
+    Resume,
+    RestartPoint,
+    RestartMatch,
+    Settings,
+    Controls,
+    Quit,
+}
+
+impl PauseAction {
+    pub const ALL: [PauseAction; 6] = [
+        PauseAction::Resume,
+        PauseAction::RestartPoint,
+        PauseAction::RestartMatch,
+        PauseAction::Settings,
+        PauseAction::Controls,
+        PauseAction::Quit,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            PauseAction::Resume => "resume",
+            PauseAction::RestartPoint => "restart point",
+            PauseAction::RestartMatch => "restart match",
+            PauseAction::Settings => "settings",
+            PauseAction::Controls => "controls",
+            PauseAction::Quit => "quit to title",
+        }
+    }
+}
+
+/// Open while the game is paused, `selected` indexes [`PauseAction::ALL`].
+#[derive(Resource, Default)]
+pub struct PauseMenu {
+    pub selected: usize,
+    pub show_controls: bool,
+}
+
+#[derive(Component)]
+pub struct PauseMenuUI;
+
+#[derive(Component)]
+pub struct PauseMenuItem(usize);
+
+#[derive(Component)]
+pub struct ControlsPanel;
+
+/// The entries stacked around the middle of the screen, the controls in small print in their
+/// place.
+pub fn spawn(mut commands: Commands) {
+    let middle = (PauseAction::ALL.len() - 1) as f32 * 0.5;
+    commands
+        .spawn((
+            SpatialBundle {
+                transform: Transform::from_xyz(0.0, 0.0, 300.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            PauseMenuUI,
+        ))
+        .with_children(|menu| {
+            for (index, action) in PauseAction::ALL.iter().enumerate() {
+                menu.spawn((
+                    SpatialBundle::from_transform(
+                        Transform::from_scale(Vec3::splat(text::PIXEL)).with_translation(
+                            Vec3::new(0.0, (middle - index as f32) * ITEM_SPACING, 0.0),
+                        ),
+                    ),
+                    BitmapText::new(action.label(), text::INK),
+                    PauseMenuItem(index),
+                ));
+            }
+            menu.spawn((
+                SpatialBundle {
+                    transform: Transform::from_scale(Vec3::splat(text::SMALL_PIXEL)),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                BitmapText::new(CONTROLS, text::INK)
+                    .with_alignment(TextAlignment::Center)
+                    .with_anchor(Anchor::Center),
+                ControlsPanel,
+            ));
+        });
+}
+
+/// Opens the menu when the game is paused and closes it once it isn't.
+pub fn open(mut commands: Commands, menu: Option<Res<PauseMenu>>, state: Query<&State>) {
+    let paused = matches!(state.single().game_state, GameState::Paused);
+    if paused && menu.is_none() {
+        commands.init_resource::<PauseMenu>();
+    } else if !paused && menu.is_some() {
+        commands.remove_resource::<PauseMenu>();
+    }
+}
+
+/// Picks a choice with the keyboard, a gamepad, the mouse or touch.
+pub fn navigate(
+    keys: Res<Input<KeyCode>>,
+    gamepad: Res<Input<GamepadButton>>,
+    mut menu: ResMut<PauseMenu>,
+    pointer: Pointer,
+    items: Query<(&PauseMenuItem, &BitmapText, &GlobalTransform)>,
+    mut hovered: Local<Option<usize>>,
+    mut moved: EventWriter<MenuMoved>,
+) -> Option<PauseAction> {
+    let confirm = keys.just_pressed(keymap::confirm())
+        || keymap::gamepad_pressed(&gamepad, GamepadButtonType::South);
+    if menu.show_controls {
+        let back = confirm
+            || keys.just_pressed(keymap::menu_close())
+            || keymap::gamepad_pressed(&gamepad, GamepadButtonType::East);
+        if back {
+            menu.show_controls = false;
+            moved.send(MenuMoved);
+        }
+        *hovered = None;
+        return None;
+    }
+    let picked = pointer.pick(
+        items
+            .iter()
+            .map(|(item, text, transform)| (item.0, text, transform)),
+    );
+    let entered = picked.filter(|(index, _)| *hovered != Some(*index));
+    *hovered = picked.map(|(index, _)| index);
+    if keys.just_pressed(keymap::menu_close())
+        || keymap::gamepad_pressed(&gamepad, GamepadButtonType::East)
+        || keymap::gamepad_pressed(&gamepad, GamepadButtonType::Start)
+    {
+        return Some(PauseAction::Resume);
+    }
+
+    let last = PauseAction::ALL.len() - 1;
+    let selected = menu.selected;
+    if keys.just_pressed(keymap::menu_up())
+        || keymap::gamepad_pressed(&gamepad, GamepadButtonType::DPadUp)
+    {
+        menu.selected = selected.saturating_sub(1);
+    }
+    if keys.just_pressed(keymap::menu_down())
+        || keymap::gamepad_pressed(&gamepad, GamepadButtonType::DPadDown)
+    {
+        menu.selected = (selected + 1).min(last);
+    }
+    match picked {
+        Some((index, true)) => return Some(PauseAction::ALL[index]),
+        // only a pointer moving onto another entry selects it
+        Some((index, false)) if entered.is_some() => menu.selected = index,
+        _ => {}
+    }
+    if menu.selected != selected {
+        moved.send(MenuMoved);
+    }
+    confirm.then(|| PauseAction::ALL[menu.selected])
+}
+
+/// Carries out the choice, restarting the same way [`crate::state::update`] does for `R`.
+#[allow(clippy::too_many_arguments)]
+pub fn choose(
+    In(action): In<Option<PauseAction>>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    tuning: Res<GameTuning>,
+    mut menu: ResMut<PauseMenu>,
+    mut recorder: ResMut<Recorder>,
+    mut ghost: ResMut<Ghost>,
+    mut buffer: ResMut<RallyBuffer>,
+    mut state: Query<&mut State>,
+    mut ball: Query<&mut Ball>,
+    mut bats: Query<&mut Bat>,
+    mut confirmed: EventWriter<MenuConfirmed>,
+    mut resumed: EventWriter<Resumed>,
+    mut served: EventWriter<Served>,
+) {
+    let Some(action) = action else {
+        return;
+    };
+    let mut state = state.single_mut();
+    let countdown = state::countdown_seconds(&settings, &tuning);
+    confirmed.send(MenuConfirmed);
+    match action {
+        PauseAction::Resume => {
+            state.play_after(countdown, false);
+            resumed.send(Resumed);
+        }
+        PauseAction::RestartPoint => {
+            state::reset_rally(&tuning, &state.server, &mut ball, &mut bats);
+            // played back and raced by the ghost at the same tick
+            if let Some(replay) = &mut recorder.replay {
+                replay.reset();
+            }
+            buffer.clear();
+            if state.play_after(countdown, true) {
+                served.send(Served {
+                    by: state.server.clone(),
+                });
+            }
+        }
+        PauseAction::RestartMatch => {
+            state.new_game();
+            state::reset_rally(&tuning, &state.server, &mut ball, &mut bats);
+            // the fixed update never sees the new game, so what it keeps is dropped here
+            recorder.replay = None;
+            ghost.run = None;
+            buffer.clear();
+            if state.start_after(countdown) {
+                served.send(Served {
+                    by: state.server.clone(),
+                });
+            }
+        }
+        PauseAction::Settings => commands.init_resource::<AudioMenu>(),
+        PauseAction::Controls => menu.show_controls = true,
+        PauseAction::Quit => {
+            state.new_game();
+            state::reset_rally(&tuning, &state.server, &mut ball, &mut bats);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_ui(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    menu: Option<Res<PauseMenu>>,
+    audio_menu: Option<Res<AudioMenu>>,
+    mut root: Query<&mut Visibility, With<PauseMenuUI>>,
+    mut items: Query<(&PauseMenuItem, &mut BitmapText, &mut Visibility), Without<PauseMenuUI>>,
+    mut controls: Query<
+        (&mut BitmapText, &mut Visibility),
+        (
+            With<ControlsPanel>,
+            Without<PauseMenuItem>,
+            Without<PauseMenuUI>,
+        ),
+    >,
+) {
+    let mut visibility = root.single_mut();
+    let Some(menu) = menu.filter(|_| audio_menu.is_none()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+    let ink = themes.current(&settings).ink;
+    let (mut controls, mut controls_visibility) = controls.single_mut();
+    *controls_visibility = if menu.show_controls {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if controls.sections[0].color != ink {
+        controls.sections[0].color = ink;
+    }
+    for (item, mut text, mut visibility) in &mut items {
+        *visibility = if menu.show_controls {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        let color = if item.0 == menu.selected {
+            ink
+        } else {
+            ink.with_a(UNSELECTED_ALPHA)
+        };
+        if text.sections[0].color != color {
+            text.sections[0].color = color;
+        }
+    }
+}
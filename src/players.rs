@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bat::Variant,
+    palette::{self, Part},
+    settings::Settings,
+    storage,
+};
+
+/// Names longer than this don't fit the scoreboard.
+pub const NAME_LENGTH: usize = 6;
+
+/// A name, empty for the default one, and the palette of a bat.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct PlayerLook {
+    pub name: String,
+    pub palette: usize,
+}
+
+/// How the players want to look, kept between runs like the volumes.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct PlayerSettings {
+    pub light: PlayerLook,
+    pub dark: PlayerLook,
+    pub ball: usize,
+}
+
+/// A line of the player setup page.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerRow {
+    Name(Variant),
+    Bat(Variant),
+    Ball,
+}
+
+impl PlayerRow {
+    pub const ALL: [PlayerRow; 5] = [
+        PlayerRow::Name(Variant::Light),
+        PlayerRow::Bat(Variant::Light),
+        PlayerRow::Name(Variant::Dark),
+        PlayerRow::Bat(Variant::Dark),
+        PlayerRow::Ball,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayerRow::Name(Variant::Light) => "light name",
+            PlayerRow::Bat(Variant::Light) => "light bat",
+            PlayerRow::Name(Variant::Dark) => "dark name",
+            PlayerRow::Bat(Variant::Dark) => "dark bat",
+            PlayerRow::Ball => "ball",
+        }
+    }
+}
+
+impl PlayerSettings {
+    pub fn look(&self, variant: &Variant) -> &PlayerLook {
+        match variant {
+            Variant::Light => &self.light,
+            Variant::Dark => &self.dark,
+        }
+    }
+
+    fn look_mut(&mut self, variant: &Variant) -> &mut PlayerLook {
+        match variant {
+            Variant::Light => &mut self.light,
+            Variant::Dark => &mut self.dark,
+        }
+    }
+
+    /// The name on the scoreboard and banners, the cpu and players without one keep theirs.
+    pub fn name<'a>(&'a self, settings: &Settings, variant: &Variant) -> &'a str {
+        let name = &self.look(variant).name;
+        if settings.is_cpu(variant) || name.is_empty() {
+            settings.player_name(variant)
+        } else {
+            name
+        }
+    }
+
+    pub fn palette(&self, part: &Part) -> usize {
+        match part {
+            Part::Bat(variant) => self.look(variant).palette,
+            Part::Ball => self.ball,
+        }
+    }
+
+    /// The color `variant`'s bat is mostly drawn in, used for their name.
+    pub fn color(&self, variant: &Variant) -> Color {
+        let colors = palette::get(self.look(variant).palette).colors;
+        match variant {
+            Variant::Light => colors[0],
+            Variant::Dark => colors[2],
+        }
+    }
+
+    /// Steps the palette in `row` through all of them, wrapping around.
+    pub fn adjust(&mut self, row: &PlayerRow, steps: isize) {
+        let palette = match row {
+            PlayerRow::Name(_) => return,
+            PlayerRow::Bat(variant) => &mut self.look_mut(variant).palette,
+            PlayerRow::Ball => &mut self.ball,
+        };
+        let count = palette::all().len() as isize;
+        *palette = (*palette as isize + steps).rem_euclid(count) as usize;
+    }
+
+    /// Adds a letter or digit to the end of `variant`'s name while there's room.
+    pub fn type_character(&mut self, variant: &Variant, character: char) {
+        type_into(&mut self.look_mut(variant).name, character);
+    }
+
+    pub fn erase(&mut self, variant: &Variant) {
+        self.look_mut(variant).name.pop();
+    }
+
+    /// What `row` is set to, as shown on the setup page.
+    pub fn value(&self, settings: &Settings, row: &PlayerRow) -> String {
+        match row {
+            PlayerRow::Name(variant) if self.look(variant).name.is_empty() => {
+                settings.player_name(variant).to_string()
+            }
+            PlayerRow::Name(variant) => self.look(variant).name.clone(),
+            PlayerRow::Bat(variant) => palette::get(self.look(variant).palette).name.clone(),
+            PlayerRow::Ball => palette::get(self.ball).name.clone(),
+        }
+    }
+}
+
+/// Adds a letter or digit to the end of `name` while there's room, the same for every name typed.
+pub fn type_into(name: &mut String, character: char) {
+    if character.is_ascii_alphanumeric() && name.len() < NAME_LENGTH {
+        name.push(character.to_ascii_uppercase());
+    }
+}
+
+pub fn save(settings: &Settings, players: &PlayerSettings) {
+    if let Some(path) = &settings.players {
+        if let Err(error) = storage::save_ron(path, players) {
+            error!("{error}");
+        }
+    }
+}
+
+pub fn load(settings: Res<Settings>, mut players: ResMut<PlayerSettings>) {
+    if let Some(path) = &settings.players {
+        match storage::load_ron(path) {
+            Ok(loaded) => *players = loaded,
+            Err(error) => warn!("{error}"),
+        }
+    }
+}
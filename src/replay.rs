@@ -0,0 +1,389 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{prelude::*, sprite::Anchor};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ball::{Arena, Ball},
+    bat::{Bat, BatInput, Variant},
+    consts, keymap,
+    settings::{Difficulty, GameMode, GameRng, MatchFormat, Settings},
+    state::{GameState, State},
+    text::{self, BitmapText},
+    theme::Themes,
+    tuning::GameTuning,
+};
+
+pub const REPLAY_VERSION: u32 = 1;
+const SPEEDS: [f32; 7] = [0.125, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const NORMAL_SPEED: usize = 3;
+
+fn pack(input: &BatInput) -> u8 {
+    input.left as u8 | (input.right as u8) << 1 | (input.run as u8) << 2 | (input.swing as u8) << 3
+}
+
+fn unpack(bits: u8) -> BatInput {
+    BatInput {
+        left: bits & 1 != 0,
+        right: bits & 1 << 1 != 0,
+        run: bits & 1 << 2 != 0,
+        swing: bits & 1 << 3 != 0,
+    }
+}
+
+/// Everything needed to play a match back: the settings it was played with and
+/// the input of both players for every tick spent playing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub version: u32,
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    pub format: MatchFormat,
+    pub seed: u64,
+    pub tuning: GameTuning,
+    /// Arena edge changes as `(tick, edge)`, the window can be resized mid match.
+    pub edges: Vec<(u32, f32)>,
+    /// Ticks before which the rally was restarted from the pause menu.
+    #[serde(default)]
+    pub resets: Vec<u32>,
+    /// Light's input in the low and Dark's in the high four bits, run length encoded as `(input, ticks)`.
+    pub inputs: Vec<(u8, u32)>,
+}
+
+impl Replay {
+    pub fn new(settings: &Settings, tuning: &GameTuning, seed: u64) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            mode: settings.mode,
+            difficulty: settings.difficulty,
+            format: settings.format,
+            seed,
+            tuning: tuning.clone(),
+            edges: Vec::new(),
+            resets: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn ticks(&self) -> usize {
+        self.inputs.iter().map(|(_, ticks)| *ticks as usize).sum()
+    }
+
+    pub fn push(&mut self, light: &BatInput, dark: &BatInput) {
+        let bits = pack(light) | pack(dark) << 4;
+        match self.inputs.last_mut() {
+            Some((last, ticks)) if *last == bits => *ticks += 1,
+            _ => self.inputs.push((bits, 1)),
+        }
+    }
+
+    pub fn set_edge(&mut self, edge: f32) {
+        let tick = self.ticks() as u32;
+        match self.edges.last_mut() {
+            Some((last_tick, last_edge)) if *last_tick == tick => *last_edge = edge,
+            Some((_, last_edge)) if *last_edge == edge => {}
+            _ => self.edges.push((tick, edge)),
+        }
+    }
+
+    pub fn edge_at(&self, tick: usize) -> Option<f32> {
+        let index = self
+            .edges
+            .partition_point(|(edge_tick, _)| *edge_tick as usize <= tick);
+        index.checked_sub(1).map(|index| self.edges[index].1)
+    }
+
+    /// Restarts the rally before the next tick is played.
+    pub fn reset(&mut self) {
+        let tick = self.ticks() as u32;
+        if self.resets.last() != Some(&tick) {
+            self.resets.push(tick);
+        }
+    }
+
+    pub fn reset_at(&self, tick: usize) -> bool {
+        self.resets.binary_search(&(tick as u32)).is_ok()
+    }
+
+    /// Inputs for every tick as `(light, dark)`.
+    pub fn expand(&self) -> Vec<(BatInput, BatInput)> {
+        self.inputs
+            .iter()
+            .flat_map(|&(bits, ticks)| {
+                (0..ticks).map(move |_| (unpack(bits & 0xf), unpack(bits >> 4)))
+            })
+            .collect()
+    }
+
+    pub fn settings(&self) -> Settings {
+        Settings {
+            mode: self.mode,
+            difficulty: self.difficulty,
+            format: self.format,
+            seed: Some(self.seed),
+            ..Settings::default()
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|error| format!("could not read {}: {error}", path.display()))?;
+        let replay: Replay = ron::from_str(&source)
+            .map_err(|error| format!("could not parse {}: {error}", path.display()))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "{} is a version {} replay, expected version {REPLAY_VERSION}",
+                path.display(),
+                replay.version
+            ));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|error| format!("could not create {}: {error}", dir.display()))?;
+        }
+        let source = ron::to_string(self).map_err(|error| error.to_string())?;
+        std::fs::write(path, source)
+            .map_err(|error| format!("could not write {}: {error}", path.display()))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Recorder {
+    pub replay: Option<Replay>,
+    pub last: Option<Replay>,
+    saved: usize,
+}
+
+#[derive(Resource)]
+pub struct Playback {
+    pub replay: Replay,
+    inputs: Vec<(BatInput, BatInput)>,
+    pub tick: usize,
+    speed: usize,
+    pub paused: bool,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            inputs: replay.expand(),
+            replay,
+            tick: 0,
+            speed: NORMAL_SPEED,
+            paused: false,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.inputs.len()
+    }
+}
+
+/// Starts recording when a match starts, reseeding the rng so the seed can be saved.
+pub fn begin(
+    settings: Res<Settings>,
+    tuning: Res<GameTuning>,
+    mut rng: ResMut<GameRng>,
+    mut recorder: ResMut<Recorder>,
+    state: Query<&State>,
+) {
+    let state = state.single();
+    if recorder.replay.is_some() || !matches!(state.game_state, GameState::Playing) {
+        return;
+    }
+    let seed = rng.0.gen();
+    rng.0 = SmallRng::seed_from_u64(seed);
+    recorder.replay = Some(Replay::new(&settings, &tuning, seed));
+}
+
+pub fn record(
+    arena: Res<Arena>,
+    mut recorder: ResMut<Recorder>,
+    state: Query<&State>,
+    bats: Query<(&Bat, &BatInput)>,
+) {
+    let state = state.single();
+    let Some(replay) = &mut recorder.replay else {
+        return;
+    };
+    if !matches!(state.game_state, GameState::Playing) {
+        return;
+    }
+    let mut light = &BatInput::default();
+    let mut dark = &BatInput::default();
+    for (bat, input) in &bats {
+        match bat.variant {
+            Variant::Light => light = input,
+            Variant::Dark => dark = input,
+        }
+    }
+    replay.set_edge(arena.edge);
+    replay.push(light, dark);
+}
+
+pub fn finish(settings: Res<Settings>, mut recorder: ResMut<Recorder>, state: Query<&State>) {
+    let state = state.single();
+    if matches!(state.game_state, GameState::NewGame) {
+        recorder.replay = None;
+        return;
+    }
+    if !state.match_over {
+        return;
+    }
+    let Some(replay) = recorder.replay.take() else {
+        return;
+    };
+    if let Some(dir) = &settings.replay_dir {
+        let path = replay_path(dir, recorder.saved);
+        match replay.save(&path) {
+            Ok(()) => info!("saved replay to {}", path.display()),
+            Err(error) => error!("{error}"),
+        }
+        recorder.saved += 1;
+    }
+    recorder.last = Some(replay);
+}
+
+fn replay_path(dir: &Path, index: usize) -> PathBuf {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    dir.join(format!("replay-{seconds}-{index}.ron"))
+}
+
+/// Feeds the recorded input to the bats instead of the keyboard or cpu.
+pub fn play(
+    mut playback: ResMut<Playback>,
+    mut arena: ResMut<Arena>,
+    mut tuning: ResMut<GameTuning>,
+    mut state: Query<&mut State>,
+    mut ball: Query<&mut Ball>,
+    mut bats: Query<(&mut Bat, &mut BatInput)>,
+) {
+    let mut state = state.single_mut();
+    if playback.finished() {
+        playback.paused = true;
+        return;
+    }
+    match state.game_state {
+        GameState::Paused | GameState::Playing => {}
+        // replays go straight to the serve, the countdown isn't recorded
+        GameState::Countdown { .. } | GameState::NewGame | GameState::Winner(_) => state.start(),
+    }
+    if !matches!(state.game_state, GameState::Playing) {
+        return;
+    }
+    if let Some(edge) = playback.replay.edge_at(playback.tick) {
+        arena.edge = edge;
+    }
+    if *tuning != playback.replay.tuning {
+        *tuning = playback.replay.tuning.clone();
+    }
+    if playback.replay.reset_at(playback.tick) {
+        for mut ball in &mut ball {
+            ball.serve(&tuning, &state.server);
+        }
+        for (mut bat, _) in &mut bats {
+            bat.reset();
+        }
+    }
+    let (light, dark) = &playback.inputs[playback.tick];
+    for (bat, mut input) in &mut bats {
+        let recorded = match bat.variant {
+            Variant::Light => light,
+            Variant::Dark => dark,
+        };
+        *input = *recorded;
+    }
+    playback.tick += 1;
+}
+
+pub fn controls(
+    keys: Res<Input<KeyCode>>,
+    mut playback: ResMut<Playback>,
+    mut time: ResMut<Time>,
+    mut fixed_time: ResMut<FixedTime>,
+) {
+    if keys.just_pressed(keymap::playback_pause()) {
+        playback.paused = !playback.paused;
+    }
+    if keys.just_pressed(keymap::restart()) {
+        playback.tick = 0;
+        playback.paused = false;
+    }
+    if keys.just_pressed(keymap::playback_faster()) {
+        playback.speed = (playback.speed + 1).min(SPEEDS.len() - 1);
+    }
+    if keys.just_pressed(keymap::playback_slower()) {
+        playback.speed = playback.speed.saturating_sub(1);
+    }
+    if playback.paused && keys.just_pressed(keymap::playback_step()) {
+        let period = fixed_time.period;
+        fixed_time.tick(period);
+    }
+
+    if playback.paused {
+        if !time.is_paused() {
+            time.pause();
+        }
+    } else {
+        time.unpause();
+        if time.relative_speed() != playback.speed() {
+            time.set_relative_speed(playback.speed());
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PlaybackUI;
+
+pub fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(text::SMALL_PIXEL))
+                .with_translation(Vec3::new(0.0, 0.0, 200.0)),
+        ),
+        BitmapText::default().with_anchor(Anchor::BottomRight),
+        PlaybackUI,
+    ));
+}
+
+pub fn update_hud(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    playback: Res<Playback>,
+    window: Query<&Window>,
+    mut hud: Query<(&mut BitmapText, &mut Transform), With<PlaybackUI>>,
+) {
+    let window = window.single();
+    let (mut text, mut transform) = hud.single_mut();
+    let padding = 0.5 * consts::SCALE;
+    transform.translation.x = window.width() * 0.5 - padding;
+    transform.translation.y = window.height() * -0.5 + padding;
+
+    let status = if playback.finished() {
+        "end".to_string()
+    } else if playback.paused {
+        "paused".to_string()
+    } else {
+        format!("{}x", playback.speed())
+    };
+    let value = format!(
+        "replay {status} {:.1}s / {:.1}s",
+        playback.tick as f32 * consts::TICK.as_secs_f32(),
+        playback.inputs.len() as f32 * consts::TICK.as_secs_f32()
+    );
+    text.set_if_neq(
+        BitmapText::new(value, themes.current(&settings).ink).with_anchor(Anchor::BottomRight),
+    );
+}
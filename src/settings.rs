@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use rand::{rngs::SmallRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::bat::Variant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(clap::ValueEnum))]
+pub enum GameMode {
+    Versus,
+    Cpu,
+    Practice,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        if cfg!(feature = "singleplayer") {
+            GameMode::Practice
+        } else {
+            GameMode::Versus
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(clap::ValueEnum))]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchFormat {
+    pub points_per_game: u32,
+    pub win_by: u32,
+    pub best_of: u32,
+}
+
+impl Default for MatchFormat {
+    fn default() -> Self {
+        Self {
+            points_per_game: 1,
+            win_by: 1,
+            best_of: 1,
+        }
+    }
+}
+
+impl MatchFormat {
+    pub fn games_to_win(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    pub fn is_game_won(&self, points: u32, opponent_points: u32) -> bool {
+        points >= self.points_per_game && points >= opponent_points + self.win_by
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Settings {
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    pub format: MatchFormat,
+    pub seed: Option<u64>,
+    pub muted: bool,
+    /// Lets the cpu play Light as well, used when running headless.
+    pub autoplay: bool,
+    /// Starts on the title screen instead of waiting for `P`.
+    pub title_screen: bool,
+    /// Counts down before every serve and after unpausing, for as long as the tuning says.
+    pub countdown: bool,
+    /// Which of the arena themes to draw the match in.
+    pub theme: usize,
+    /// Where a replay of every finished match is saved.
+    pub replay_dir: Option<PathBuf>,
+    /// Where the high score table is kept, the key into local storage on the web.
+    pub high_scores: Option<PathBuf>,
+    /// Where finished matches are written as json and appended to a csv.
+    pub export_dir: Option<PathBuf>,
+    /// Where the volume settings are kept, the key into local storage on the web.
+    pub audio: Option<PathBuf>,
+    /// Where the player names and colors are kept, the key into local storage on the web.
+    pub players: Option<PathBuf>,
+}
+
+impl Settings {
+    pub fn has_player(&self, variant: &Variant) -> bool {
+        match variant {
+            Variant::Light => true,
+            Variant::Dark => self.mode != GameMode::Practice,
+        }
+    }
+
+    pub fn is_cpu(&self, variant: &Variant) -> bool {
+        match variant {
+            Variant::Light => self.autoplay,
+            Variant::Dark => self.autoplay || self.mode == GameMode::Cpu,
+        }
+    }
+
+    /// The name shown for `variant` on the scoreboard.
+    pub fn player_name(&self, variant: &Variant) -> &'static str {
+        match variant {
+            _ if self.is_cpu(variant) => "CPU",
+            Variant::Light => "LIGHT",
+            Variant::Dark => "DARK",
+        }
+    }
+
+    /// Whether a person rather than the cpu plays in this match.
+    pub fn has_human(&self) -> bool {
+        [Variant::Light, Variant::Dark]
+            .iter()
+            .any(|variant| self.has_player(variant) && !self.is_cpu(variant))
+    }
+}
+
+#[derive(Resource)]
+pub struct GameRng(pub SmallRng);
+
+impl GameRng {
+    pub fn new(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Self(SmallRng::seed_from_u64(seed)),
+            None => Self(SmallRng::from_entropy()),
+        }
+    }
+}
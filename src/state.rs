@@ -1,27 +1,50 @@
 use bevy::{prelude::*, time::Stopwatch};
+use serde::Serialize;
 
 use crate::{
-    audio::Music,
     ball::Ball,
-    bat::{Bat, Direction, Variant},
-    consts, keymap,
+    bat::{Bat, Variant},
+    consts,
+    events::{BallHit, CountdownTick, MatchOver, Paused, PointScored, Resumed, Served},
+    keymap,
+    players::PlayerSettings,
+    settings::{GameMode, MatchFormat, Settings},
+    text::{BitmapSection, BitmapText},
+    theme::Themes,
+    title::Screen,
+    tuning::GameTuning,
 };
 
+#[derive(Debug, PartialEq)]
 pub enum GameState {
     Paused,
     NewGame,
+    /// Counting down before play starts, `serve` is whether the ball is served when it does.
+    Countdown {
+        serve: bool,
+    },
     Playing,
     Winner(Variant),
 }
 
-impl GameState {
-    fn sprite_index(&self) -> usize {
-        match self {
-            GameState::Paused => 0,
-            GameState::NewGame => 1,
-            GameState::Winner(Variant::Light) => 2,
-            GameState::Winner(Variant::Dark) => 3,
-            GameState::Playing => unreachable!(),
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Score {
+    pub light: u32,
+    pub dark: u32,
+}
+
+impl Score {
+    pub fn get(&self, variant: &Variant) -> u32 {
+        match variant {
+            Variant::Light => self.light,
+            Variant::Dark => self.dark,
+        }
+    }
+
+    fn increment(&mut self, variant: &Variant) {
+        match variant {
+            Variant::Light => self.light += 1,
+            Variant::Dark => self.dark += 1,
         }
     }
 }
@@ -31,6 +54,16 @@ pub struct State {
     pub game_state: GameState,
     pub game_time: Stopwatch,
     pub hits_with_velocity: f32,
+    pub server: Variant,
+    /// Who served first in this game, the other player serves first in the next one.
+    pub first_server: Variant,
+    pub points: Score,
+    pub games: Score,
+    /// Final points of every finished game in this match.
+    pub game_scores: Vec<Score>,
+    pub match_over: bool,
+    /// Time left in [`GameState::Countdown`].
+    pub countdown: Timer,
 }
 
 impl Default for State {
@@ -39,6 +72,13 @@ impl Default for State {
             game_state: GameState::NewGame,
             game_time: Stopwatch::new(),
             hits_with_velocity: 0.0,
+            server: Variant::Dark,
+            first_server: Variant::Dark,
+            points: Score::default(),
+            games: Score::default(),
+            game_scores: Vec::new(),
+            match_over: false,
+            countdown: Timer::default(),
         }
     }
 }
@@ -47,103 +87,294 @@ impl State {
     pub fn game_over(&mut self, pause_state: GameState) {
         self.game_state = pause_state;
     }
+
+    /// Back to the menu. Dark serves first in every match, replays are played back that way.
+    pub fn new_game(&mut self) {
+        self.game_state = GameState::NewGame;
+        self.server = Variant::Dark;
+        self.first_server = Variant::Dark;
+    }
+
+    pub fn score_point(&mut self, winner: Variant, format: &MatchFormat) {
+        self.points.increment(&winner);
+        let opponent = winner.opponent();
+        if format.is_game_won(self.points.get(&winner), self.points.get(&opponent)) {
+            self.games.increment(&winner);
+            self.game_scores.push(self.points);
+            self.points = Score::default();
+            self.match_over = self.games.get(&winner) >= format.games_to_win();
+            // the players switch ends, so the first to receive serves first in the next game
+            self.first_server = self.first_server.opponent();
+            self.server = self.first_server.clone();
+        } else if self.serve_changes(format) {
+            self.server = self.server.opponent();
+        }
+        if self.match_over {
+            self.server = Variant::Dark;
+            self.first_server = Variant::Dark;
+        }
+        self.game_over(GameState::Winner(winner));
+    }
+
+    /// Serves change every two points, and every point once both players could win the game
+    /// with the next one but need to win by more.
+    fn serve_changes(&self, format: &MatchFormat) -> bool {
+        let deuce = format.win_by > 1
+            && self.points.light + 1 >= format.points_per_game
+            && self.points.dark + 1 >= format.points_per_game;
+        deuce || (self.points.light + self.points.dark).is_multiple_of(2)
+    }
+
+    /// Whether `variant` takes the match by winning the next point.
+    pub fn match_point(&self, variant: &Variant, format: &MatchFormat) -> bool {
+        let points = self.points.get(variant) + 1;
+        format.is_game_won(points, self.points.get(&variant.opponent()))
+            && self.games.get(variant) + 1 >= format.games_to_win()
+    }
+
+    /// Starts playing from the menu, beginning a new match if the last one is over.
+    pub fn start(&mut self) {
+        self.start_after(0.0);
+    }
+
+    /// Like [`State::start`], counting down for `seconds` first.
+    /// Returns whether play started right away.
+    pub fn start_after(&mut self, seconds: f32) -> bool {
+        if matches!(self.game_state, GameState::NewGame) || self.match_over {
+            self.hits_with_velocity = 0.0;
+            self.game_time.reset();
+            self.points = Score::default();
+            self.games = Score::default();
+            self.game_scores.clear();
+            self.match_over = false;
+        }
+        self.play_after(seconds, true)
+    }
+
+    /// Plays after counting down for `seconds`, or right away when that's zero.
+    /// Returns whether play started right away.
+    pub fn play_after(&mut self, seconds: f32, serve: bool) -> bool {
+        if seconds <= 0.0 {
+            self.game_state = GameState::Playing;
+            return true;
+        }
+        self.countdown = Timer::from_seconds(seconds, TimerMode::Once);
+        self.game_state = GameState::Countdown { serve };
+        false
+    }
+
+    /// The number shown while counting down, 0 when not.
+    pub fn countdown_number(&self) -> u32 {
+        match self.game_state {
+            GameState::Countdown { .. } => self.countdown.remaining_secs().ceil() as u32,
+            _ => 0,
+        }
+    }
+}
+
+/// How long to count down before serving and after unpausing, zero when it's turned off.
+pub fn countdown_seconds(settings: &Settings, tuning: &GameTuning) -> f32 {
+    if settings.countdown {
+        tuning.countdown.max(0.0)
+    } else {
+        0.0
+    }
 }
 
 #[derive(Component)]
 pub struct ControlsUI;
 
-pub fn spawn(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+/// How far the new game and winner text has bobbed up or down.
+pub fn bob(time: &Time) -> f32 {
+    (time.elapsed_seconds() * 2.0).sin() * consts::SCALE
+}
+
+/// Serves the ball again and puts the bats back, for every restart.
+pub fn reset_rally(
+    tuning: &GameTuning,
+    server: &Variant,
+    ball: &mut Query<&mut Ball>,
+    bats: &mut Query<&mut Bat>,
+) {
+    for mut ball in ball {
+        ball.serve(tuning, server);
+    }
+    for mut bat in bats {
+        bat.reset();
+    }
+}
+
+/// Runs the match clock, and the countdown until it's done and play starts.
+pub fn tick(
+    fixed_time: Res<FixedTime>,
+    mut state: Query<&mut State>,
+    mut served: EventWriter<Served>,
+    mut ticks: EventWriter<CountdownTick>,
 ) {
-    let state = State::default();
+    let mut state = state.single_mut();
+    match state.game_state {
+        GameState::Playing => {
+            state.game_time.tick(fixed_time.period);
+        }
+        GameState::Countdown { serve } => {
+            let shown = (!state.countdown.elapsed().is_zero()).then(|| state.countdown_number());
+            state.countdown.tick(fixed_time.period);
+            if state.countdown.finished() {
+                state.game_state = GameState::Playing;
+                if serve {
+                    served.send(Served {
+                        by: state.server.clone(),
+                    });
+                }
+            } else if shown != Some(state.countdown_number()) {
+                ticks.send(CountdownTick {
+                    number: state.countdown_number(),
+                });
+            }
+        }
+        GameState::Paused | GameState::NewGame | GameState::Winner(_) => {}
+    }
+}
 
-    let texture_handle = asset_server.load("text.png");
-    let texture_atlas =
-        TextureAtlas::from_grid(texture_handle, Vec2::new(32.0, 32.0), 1, 5, None, None);
-    let texture_atlas = texture_atlases.add(texture_atlas);
+/// Keeps the score from the hits and points the ball reports, right after [`crate::ball::update`],
+/// and serves the next point.
+#[allow(clippy::too_many_arguments)]
+pub fn score(
+    settings: Res<Settings>,
+    tuning: Res<GameTuning>,
+    mut hits: EventReader<BallHit>,
+    mut points: EventReader<PointScored>,
+    mut match_over: EventWriter<MatchOver>,
+    mut state: Query<&mut State>,
+    mut ball: Query<&mut Ball>,
+    mut bats: Query<&mut Bat>,
+) {
+    let mut state = state.single_mut();
+    for hit in hits.iter() {
+        state.hits_with_velocity += hit.velocity.x.abs() + hit.velocity.y.abs();
+    }
+    for point in points.iter() {
+        info!(
+            "score: {} in {}s",
+            state.hits_with_velocity,
+            state.game_time.elapsed_secs()
+        );
+        state.score_point(point.winner.clone(), &settings.format);
+        // the wall serves every ball in practice
+        if !settings.has_player(&Variant::Dark) {
+            state.server = Variant::Dark;
+        }
+        reset_rally(&tuning, &state.server, &mut ball, &mut bats);
+        if state.match_over {
+            match_over.send(MatchOver {
+                winner: point.winner.clone(),
+            });
+        }
+    }
+}
 
+pub fn spawn(mut commands: Commands) {
     commands.spawn((
-        SpriteSheetBundle {
-            texture_atlas,
-            transform: Transform::from_scale(Vec3::splat(1.0 * consts::SCALE))
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(1.0 * consts::SCALE))
                 .with_translation(Vec3::new(0.0, 0.0, 100.0)),
-            ..default()
-        },
-        state,
+        ),
+        BitmapText::default().with_alignment(TextAlignment::Center),
+        State::default(),
     ));
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update(
     time: Res<Time>,
     keys: Res<Input<KeyCode>>,
-    mut state: Query<(&mut State, &mut Transform, &mut TextureAtlasSprite)>,
+    settings: Res<Settings>,
+    tuning: Res<GameTuning>,
+    mut state: Query<(&mut State, &mut Transform)>,
     mut ball: Query<&mut Ball>,
     mut bats: Query<&mut Bat>,
-    music_controller: Query<(&AudioSink, &Music)>,
+    mut paused: EventWriter<Paused>,
+    mut resumed: EventWriter<Resumed>,
+    mut served: EventWriter<Served>,
+    screen: Option<Res<Screen>>,
 ) {
-    let (mut state, mut transform, mut menu_sprite) = state.single_mut();
+    let (mut state, mut transform) = state.single_mut();
+    let countdown = countdown_seconds(&settings, &tuning);
     if keys.just_pressed(keymap::pause()) {
-        state.game_state = match state.game_state {
-            GameState::Paused => GameState::Playing,
-            GameState::Playing => GameState::Paused,
+        match state.game_state {
+            GameState::Paused => {
+                state.play_after(countdown, false);
+                resumed.send(Resumed);
+            }
+            GameState::Playing => {
+                state.game_state = GameState::Paused;
+                paused.send(Paused);
+            }
+            // there's no one to wait for in practice, [`tick`] serves on the next tick
+            GameState::Countdown { .. } if settings.mode == GameMode::Practice => {
+                let duration = state.countdown.duration();
+                state.countdown.set_elapsed(duration);
+            }
+            GameState::Countdown { .. } => {}
             GameState::NewGame | GameState::Winner(_) => {
-                state.hits_with_velocity = 0.0;
-                state.game_time.reset();
-                GameState::Playing
+                if state.start_after(countdown) {
+                    served.send(Served {
+                        by: state.server.clone(),
+                    });
+                }
             }
         }
     }
 
     if keys.just_pressed(keymap::restart()) {
-        for mut ball in &mut ball {
-            let Ball {
-                position,
-                velocity,
-                last_hit,
-                hit_edge: _,
-            } = Ball::default();
-            ball.position = position;
-            ball.velocity = velocity;
-            ball.last_hit = last_hit;
-        }
-        for mut bat in &mut bats {
-            bat.swinging = Direction::None;
-            bat.position_x = 0.0;
-        }
-        state.game_over(GameState::NewGame);
+        state.new_game();
+        reset_rally(&tuning, &state.server, &mut ball, &mut bats);
     };
-    if matches!(state.game_state, GameState::Playing) {
-        state.game_time.tick(time.delta());
-    }
-    let elapsed = state.game_time.elapsed_secs();
-    let music_two_volume = (elapsed * 0.5 - 48.0).clamp(0.0, 1.0);
-    let music_one_volume = (elapsed * 0.5 - 16.0).clamp(0.0, 1.0) - music_two_volume;
-    let music_zero_volume = (elapsed * 0.5).clamp(0.0, 1.0) - music_one_volume - music_two_volume;
-    for (&ref sink, &ref music_state) in &music_controller {
-        match &music_state {
-            Music::Zero if matches!(state.game_state, GameState::Playing) => {
-                sink.set_volume(music_zero_volume)
-            }
-            Music::One if matches!(state.game_state, GameState::Playing) => {
-                sink.set_volume(music_one_volume)
-            }
-            Music::Two if matches!(state.game_state, GameState::Playing) => {
-                sink.set_volume(music_two_volume)
-            }
-            _ => sink.set_volume(0.0),
-        }
-    }
     match &state.game_state {
-        game_state @ (GameState::Paused | GameState::NewGame | GameState::Winner(_)) => {
-            let offset = (time.elapsed_seconds() * 2.0).sin() * consts::SCALE;
+        // the title screen is shown instead
+        GameState::NewGame if screen.is_some() => transform.scale = Vec3::ZERO,
+        GameState::NewGame | GameState::Winner(_) => {
+            let offset = bob(&time);
 
             transform.scale = Vec3::splat(1.0 * consts::SCALE);
             transform.translation =
                 Vec3::new(transform.translation.x, offset, transform.translation.z);
-            menu_sprite.index = game_state.sprite_index();
         }
-        GameState::Playing => transform.scale = Vec3::ZERO,
+        // the pause menu and the countdown are shown instead
+        GameState::Countdown { .. } | GameState::Playing | GameState::Paused => {
+            transform.scale = Vec3::ZERO
+        }
     }
 }
+
+/// Says it's a new game or who won, in the theme's ink and the winner's color.
+pub fn update_message(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    players: Res<PlayerSettings>,
+    mut state: Query<(&State, &mut BitmapText)>,
+) {
+    let (state, mut text) = state.single_mut();
+    let theme = themes.current(&settings);
+    let sections = match &state.game_state {
+        GameState::NewGame => vec![BitmapSection {
+            value: "NEW GAME".to_string(),
+            color: theme.ink,
+        }],
+        GameState::Winner(variant) => vec![
+            BitmapSection {
+                value: "WINNER\n".to_string(),
+                color: theme.ink,
+            },
+            BitmapSection {
+                value: players.name(&settings, variant).to_string(),
+                color: theme.readable(players.color(variant)),
+            },
+        ],
+        GameState::Countdown { .. } | GameState::Playing | GameState::Paused => Vec::new(),
+    };
+    text.set_if_neq(BitmapText {
+        sections,
+        ..text.clone()
+    });
+}
@@ -0,0 +1,243 @@
+use std::fmt::Write;
+
+use bevy::{prelude::*, sprite::Anchor};
+use serde::Serialize;
+
+use crate::{
+    bat::{Bat, Variant},
+    consts,
+    events::{BallHit, FastestShot, PointScored, PointStreak},
+    instant_replay::InstantReplay,
+    players::PlayerSettings,
+    settings::Settings,
+    state::{GameState, State},
+    text::{self, BitmapText},
+    theme::Themes,
+    tuning::GameTuning,
+};
+
+/// Share of the max ball speed a hit needs to count as a smash.
+const SMASH_SPEED: f32 = 0.8;
+const COLUMN_WIDTH: f32 = 8.0 * consts::SCALE;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct PlayerStats {
+    pub hits: u32,
+    pub longest_rally: u32,
+    pub fastest_shot: f32,
+    /// Sum of how far from the bat's center every hit was, see [`PlayerStats::average_offset`].
+    pub total_offset: f32,
+    pub smashes: u32,
+    pub misses: u32,
+    pub distance: f32,
+}
+
+impl PlayerStats {
+    pub fn average_offset(&self) -> f32 {
+        if self.hits == 0 {
+            return 0.0;
+        }
+        self.total_offset / self.hits as f32
+    }
+}
+
+/// Per player stats for the current match, reset when a new match starts.
+#[derive(Resource, Default, Debug)]
+pub struct MatchStats {
+    pub light: PlayerStats,
+    pub dark: PlayerStats,
+    rally: [u32; 2],
+    streak: [u32; 2],
+    bat_positions: [Option<f32>; 2],
+}
+
+fn index(variant: &Variant) -> usize {
+    match variant {
+        Variant::Light => 0,
+        Variant::Dark => 1,
+    }
+}
+
+impl MatchStats {
+    pub fn get(&self, variant: &Variant) -> &PlayerStats {
+        match variant {
+            Variant::Light => &self.light,
+            Variant::Dark => &self.dark,
+        }
+    }
+
+    fn get_mut(&mut self, variant: &Variant) -> &mut PlayerStats {
+        match variant {
+            Variant::Light => &mut self.light,
+            Variant::Dark => &mut self.dark,
+        }
+    }
+
+    /// Whether the hit was a smash faster than any shot before it.
+    pub fn record_hit(&mut self, hit: &BallHit, max_ball_speed: f32) -> bool {
+        let record = self.light.fastest_shot.max(self.dark.fastest_shot);
+        self.rally[index(&hit.by)] += 1;
+        let rally = self.rally[index(&hit.by)];
+        let speed = hit.velocity.length();
+        let player = self.get_mut(&hit.by);
+        player.hits += 1;
+        player.longest_rally = player.longest_rally.max(rally);
+        player.fastest_shot = player.fastest_shot.max(speed);
+        player.total_offset += hit.offset.abs();
+        let smash = hit.velocity.y.abs() >= max_ball_speed * SMASH_SPEED;
+        if smash {
+            player.smashes += 1;
+        }
+        smash && record > 0.0 && speed > record
+    }
+
+    /// How many points in a row the winner has now won.
+    pub fn record_point(&mut self, point: &PointScored) -> u32 {
+        self.get_mut(&point.winner.opponent()).misses += 1;
+        self.rally = [0; 2];
+        self.streak[index(&point.winner.opponent())] = 0;
+        self.streak[index(&point.winner)] += 1;
+        self.streak[index(&point.winner)]
+    }
+
+    pub fn record_bat(&mut self, variant: &Variant, position_x: f32, moving: bool) {
+        let last = self.bat_positions[index(variant)].replace(position_x);
+        if let (Some(last), true) = (last, moving) {
+            self.get_mut(variant).distance += (position_x - last).abs();
+        }
+    }
+}
+
+/// A column of the stats table, the labels are column 0 and every player gets one after.
+#[derive(Component)]
+pub struct StatsUI(usize);
+
+/// Measures how far the bats move, run right after [`crate::bat::update`] so resets aren't counted.
+pub fn track_bats(mut stats: ResMut<MatchStats>, state: Query<&State>, bats: Query<&Bat>) {
+    let state = state.single();
+    let playing = matches!(state.game_state, GameState::Playing);
+    if matches!(state.game_state, GameState::NewGame)
+        || (playing && state.game_time.elapsed().is_zero())
+    {
+        *stats = MatchStats::default();
+    }
+    for bat in &bats {
+        stats.record_bat(&bat.variant, bat.position_x, playing);
+    }
+}
+
+pub fn collect(
+    tuning: Res<GameTuning>,
+    mut stats: ResMut<MatchStats>,
+    mut hits: EventReader<BallHit>,
+    mut points: EventReader<PointScored>,
+    mut fastest: EventWriter<FastestShot>,
+    mut streaks: EventWriter<PointStreak>,
+) {
+    for hit in hits.iter() {
+        if stats.record_hit(hit, tuning.max_ball_speed) {
+            fastest.send(FastestShot {
+                by: hit.by.clone(),
+                speed: hit.velocity.length(),
+            });
+        }
+    }
+    for point in points.iter() {
+        let points = stats.record_point(point);
+        if points >= 2 {
+            streaks.send(PointStreak {
+                by: point.winner.clone(),
+                points,
+            });
+        }
+    }
+}
+
+pub fn spawn(mut commands: Commands) {
+    for column in 0..=2 {
+        commands.spawn((
+            SpatialBundle::from_transform(
+                Transform::from_scale(Vec3::splat(text::SMALL_PIXEL))
+                    .with_translation(Vec3::new(0.0, 0.0, 200.0)),
+            ),
+            column_text(column, String::new(), text::INK),
+            StatsUI(column),
+        ));
+    }
+}
+
+/// The labels line up on the left, the numbers on the right.
+fn column_text(column: usize, value: String, ink: Color) -> BitmapText {
+    let alignment = if column == 0 {
+        TextAlignment::Left
+    } else {
+        TextAlignment::Right
+    };
+    BitmapText::new(value, ink)
+        .with_alignment(alignment)
+        .with_anchor(Anchor::CenterRight)
+}
+
+/// The stats table shown on the winner screen, the labels followed by a column per player.
+pub fn columns(stats: &MatchStats, players: &[(Variant, &str)]) -> Vec<String> {
+    let rows: [(&str, fn(&PlayerStats) -> String); 7] = [
+        ("hits", |player| player.hits.to_string()),
+        ("longest rally", |player| player.longest_rally.to_string()),
+        ("fastest shot", |player| {
+            format!("{:.1}", player.fastest_shot)
+        }),
+        ("avg offset", |player| {
+            format!("{:.2}", player.average_offset())
+        }),
+        ("smashes", |player| player.smashes.to_string()),
+        ("misses", |player| player.misses.to_string()),
+        ("bat distance", |player| format!("{:.1}", player.distance)),
+    ];
+    let mut columns = vec![String::new()];
+    for (name, _) in &rows {
+        write!(columns[0], "\n{name}").expect("writing to a string");
+    }
+    for (player, name) in players {
+        let mut column = name.to_string();
+        for (_, value) in &rows {
+            write!(column, "\n{}", value(stats.get(player))).expect("writing to a string");
+        }
+        columns.push(column);
+    }
+    columns
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_ui(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    player_settings: Res<PlayerSettings>,
+    stats: Res<MatchStats>,
+    instant_replay: Option<Res<InstantReplay>>,
+    state: Query<&State>,
+    window: Query<&Window>,
+    mut ui: Query<(&StatsUI, &mut BitmapText, &mut Transform)>,
+) {
+    let state = state.single();
+    let window = window.single();
+    let players: Vec<(Variant, &str)> = [Variant::Light, Variant::Dark]
+        .into_iter()
+        .filter(|variant| settings.has_player(variant))
+        .map(|variant| {
+            let name = player_settings.name(&settings, &variant);
+            (variant, name)
+        })
+        .collect();
+    let columns = match state.game_state {
+        GameState::Winner(_) if instant_replay.is_none() => columns(&stats, &players),
+        _ => Vec::new(),
+    };
+    let ink = themes.current(&settings).ink;
+    let right = window.width() * 0.5 - consts::SCALE;
+    for (column, mut text, mut transform) in &mut ui {
+        let offset = players.len().saturating_sub(column.0) as f32;
+        transform.translation.x = right - offset * COLUMN_WIDTH;
+        let value = columns.get(column.0).cloned().unwrap_or_default();
+        text.set_if_neq(column_text(column.0, value, ink));
+    }
+}
@@ -0,0 +1,67 @@
+//! Small save files, kept on disk natively and in local storage on the web.
+
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads a RON save file, the default value when there's none yet.
+pub fn load_ron<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    match read(path)? {
+        Some(source) => ron::from_str(&source)
+            .map_err(|error| format!("could not parse {}: {error}", path.display())),
+        None => Ok(T::default()),
+    }
+}
+
+pub fn save_ron<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let source = ron::to_string(value).map_err(|error| error.to_string())?;
+    write(path, &source)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(path: &Path) -> Result<Option<String>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(format!("could not read {}: {error}", path.display())),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|error| format!("could not create {}: {error}", dir.display()))?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|error| format!("could not write {}: {error}", path.display()))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .ok_or("no browser window")?
+        .local_storage()
+        .ok()
+        .flatten()
+        .ok_or_else(|| "local storage is unavailable".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn key(path: &Path) -> String {
+    format!("bordtennis/{}", path.display())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(path: &Path) -> Result<Option<String>, String> {
+    local_storage()?
+        .get_item(&key(path))
+        .map_err(|_| format!("could not read {}", key(path)))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(path: &Path, contents: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(&key(path), contents)
+        .map_err(|_| format!("could not write {}", key(path)))
+}
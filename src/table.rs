@@ -1,12 +1,63 @@
 use bevy::prelude::*;
 
-use crate::consts;
+use crate::{consts, settings::Settings, theme::Themes};
+
+/// Where the net is on the table sprite, from its center.
+const NET_OFFSET: f32 = 3.0;
+
+#[derive(Component)]
+pub struct Table;
+
+#[derive(Component)]
+pub struct Net;
 
 pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(SpriteBundle {
-        texture: asset_server.load("table.png"),
-        transform: Transform::from_scale(Vec3::splat(1.0 * consts::SCALE))
-            .with_translation(Vec3::new(0.0, -2.0 * consts::SCALE, 0.0)),
-        ..default()
-    });
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: asset_server.load("table.png"),
+                transform: Transform::from_scale(Vec3::splat(1.0 * consts::SCALE))
+                    .with_translation(Vec3::new(0.0, -2.0 * consts::SCALE, 0.0)),
+                ..default()
+            },
+            Table,
+        ))
+        .with_children(|table| {
+            table.spawn((
+                SpriteBundle {
+                    transform: Transform::from_translation(Vec3::new(0.0, NET_OFFSET, 1.0)),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                Net,
+            ));
+        });
+}
+
+/// Draws the table and net of the picked theme.
+pub fn apply_theme(
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    mut table: Query<&mut Handle<Image>, (With<Table>, Without<Net>)>,
+    mut net: Query<(&mut Handle<Image>, &mut Sprite, &mut Visibility), With<Net>>,
+) {
+    if !settings.is_changed() && !themes.is_changed() {
+        return;
+    }
+    let theme = themes.current(&settings);
+    let texture = asset_server.load(&theme.table);
+    let mut table = table.single_mut();
+    if *table != texture {
+        *table = texture;
+    }
+    let (mut net_texture, mut sprite, mut visibility) = net.single_mut();
+    match &theme.net {
+        Some(style) => {
+            *net_texture = asset_server.load(&style.sprite);
+            sprite.color = style.color;
+            *visibility = Visibility::Inherited;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
 }
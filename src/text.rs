@@ -0,0 +1,352 @@
+use std::ops::Range;
+
+use bevy::{ecs::system::SystemParam, input::touch::Touches, prelude::*, sprite::Anchor};
+
+use crate::consts;
+
+/// Size of one font pixel on screen, the same as the other sprites' pixels.
+pub const PIXEL: f32 = 0.5 * consts::SCALE;
+/// Font pixels of small print like the tables, half a [`PIXEL`].
+pub const SMALL_PIXEL: f32 = 0.5 * PIXEL;
+/// Width of a glyph plus the gap after it, in font pixels.
+pub const ADVANCE: u32 = 5;
+/// Height of a line plus the gap under it, in font pixels.
+pub const LINE_HEIGHT: u32 = 7;
+/// The darkest color of the palette.
+pub const INK: Color = Color::rgb(43.0 / 255.0, 20.0 / 255.0, 126.0 / 255.0);
+/// The palette's lighter purple, used for less important text.
+pub const FADED: Color = Color::rgb(123.0 / 255.0, 105.0 / 255.0, 192.0 / 255.0);
+
+const CELL: Vec2 = Vec2::new(6.0, 8.0);
+const COLUMNS: usize = 16;
+const ROWS: usize = 4;
+const FIRST: char = ' ';
+const LAST: char = '_';
+
+/// Where a glyph is in `font.png`, lower case letters use the capitals.
+pub fn glyph_index(character: char) -> usize {
+    let character = character.to_ascii_uppercase();
+    let character = if (FIRST..=LAST).contains(&character) {
+        character
+    } else {
+        '?'
+    };
+    character as usize - FIRST as usize
+}
+
+#[derive(Resource)]
+pub struct BitmapFont {
+    pub atlas: Handle<TextureAtlas>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapSection {
+    pub value: String,
+    pub color: Color,
+}
+
+/// Text drawn with `font.png`, one sprite per glyph, scaled by the entity's transform.
+#[derive(Component, Clone, Debug)]
+pub struct BitmapText {
+    pub sections: Vec<BitmapSection>,
+    pub alignment: TextAlignment,
+    pub anchor: Anchor,
+    /// Wraps between words to stay within this many glyphs.
+    pub max_columns: Option<usize>,
+}
+
+impl Default for BitmapText {
+    fn default() -> Self {
+        Self {
+            sections: Vec::new(),
+            alignment: TextAlignment::Left,
+            anchor: Anchor::Center,
+            max_columns: None,
+        }
+    }
+}
+
+// `Anchor` can't be compared itself
+impl PartialEq for BitmapText {
+    fn eq(&self, other: &Self) -> bool {
+        self.sections == other.sections
+            && self.alignment == other.alignment
+            && self.anchor.as_vec() == other.anchor.as_vec()
+            && self.max_columns == other.max_columns
+    }
+}
+
+impl BitmapText {
+    pub fn new(value: impl Into<String>, color: Color) -> Self {
+        Self {
+            sections: vec![BitmapSection {
+                value: value.into(),
+                color,
+            }],
+            ..default()
+        }
+    }
+
+    pub fn with_alignment(self, alignment: TextAlignment) -> Self {
+        Self { alignment, ..self }
+    }
+
+    pub fn with_anchor(self, anchor: Anchor) -> Self {
+        Self { anchor, ..self }
+    }
+
+    pub fn with_max_columns(self, max_columns: usize) -> Self {
+        Self {
+            max_columns: Some(max_columns),
+            ..self
+        }
+    }
+
+    fn characters(&self) -> Vec<(char, Color)> {
+        self.sections
+            .iter()
+            .flat_map(|section| section.value.chars().map(|c| (c, section.color)))
+            .collect()
+    }
+
+    /// Width and height in font pixels.
+    pub fn size(&self) -> UVec2 {
+        let characters: Vec<char> = self.characters().into_iter().map(|(c, _)| c).collect();
+        let lines = wrap_ranges(&characters, self.max_columns);
+        block_size(&lines)
+    }
+
+    /// The top left corner of the block relative to the anchor, in font pixels.
+    fn origin(&self, size: Vec2) -> Vec2 {
+        (-(self.anchor.as_vec() + Vec2::new(0.5, -0.5)) * size).floor()
+    }
+
+    /// Whether `point` in the world is on the text drawn at `transform`.
+    pub fn contains(&self, transform: &GlobalTransform, point: Vec2) -> bool {
+        let local = transform
+            .affine()
+            .inverse()
+            .transform_point3(point.extend(0.0));
+        let size = self.size().as_vec2();
+        let origin = self.origin(size);
+        (origin.x..=origin.x + size.x).contains(&local.x)
+            && (origin.y - size.y..=origin.y).contains(&local.y)
+    }
+
+    /// Every glyph with the position of its top left corner relative to the anchor, in font pixels.
+    pub fn layout(&self) -> Vec<PlacedGlyph> {
+        let characters = self.characters();
+        let plain: Vec<char> = characters.iter().map(|(c, _)| *c).collect();
+        let lines = wrap_ranges(&plain, self.max_columns);
+        let size = block_size(&lines).as_vec2();
+        let origin = self.origin(size);
+        let mut glyphs = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            let width = line_width(line.len()) as f32;
+            let x = match self.alignment {
+                TextAlignment::Left => 0.0,
+                TextAlignment::Center => ((size.x - width) * 0.5).floor(),
+                TextAlignment::Right => size.x - width,
+            };
+            for (column, index) in line.clone().enumerate() {
+                let (character, color) = characters[index];
+                if character == ' ' {
+                    continue;
+                }
+                glyphs.push(PlacedGlyph {
+                    index,
+                    atlas_index: glyph_index(character),
+                    color,
+                    position: origin
+                        + Vec2::new(
+                            x + (column as u32 * ADVANCE) as f32,
+                            -((row as u32 * LINE_HEIGHT) as f32),
+                        ),
+                });
+            }
+        }
+        glyphs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacedGlyph {
+    /// Which character of the text this is.
+    pub index: usize,
+    pub atlas_index: usize,
+    pub color: Color,
+    pub position: Vec2,
+}
+
+fn line_width(glyphs: usize) -> u32 {
+    (glyphs as u32 * ADVANCE).saturating_sub(1)
+}
+
+fn block_size(lines: &[Range<usize>]) -> UVec2 {
+    let columns = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    UVec2::new(
+        line_width(columns),
+        (lines.len() as u32 * LINE_HEIGHT).saturating_sub(1),
+    )
+}
+
+/// Splits `text` into lines on newlines and between words, breaking words longer than a line.
+pub fn wrap(text: &str, max_columns: Option<usize>) -> Vec<String> {
+    let characters: Vec<char> = text.chars().collect();
+    wrap_ranges(&characters, max_columns)
+        .into_iter()
+        .map(|line| characters[line].iter().collect())
+        .collect()
+}
+
+fn wrap_ranges(characters: &[char], max_columns: Option<usize>) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = characters[start..]
+            .iter()
+            .position(|c| *c == '\n')
+            .map_or(characters.len(), |offset| start + offset);
+        wrap_paragraph(characters, start..end, max_columns, &mut lines);
+        if end == characters.len() {
+            return lines;
+        }
+        start = end + 1;
+    }
+}
+
+fn wrap_paragraph(
+    characters: &[char],
+    paragraph: Range<usize>,
+    max_columns: Option<usize>,
+    lines: &mut Vec<Range<usize>>,
+) {
+    let Some(max_columns) = max_columns.map(|columns| columns.max(1)) else {
+        lines.push(paragraph);
+        return;
+    };
+    let mut start = paragraph.start;
+    while paragraph.end - start > max_columns {
+        let limit = start + max_columns;
+        match characters[start..=limit].iter().rposition(|c| *c == ' ') {
+            Some(space) if space > 0 => {
+                lines.push(start..start + space);
+                start += space + 1;
+            }
+            _ => {
+                lines.push(start..limit);
+                start = limit;
+            }
+        }
+    }
+    lines.push(start..paragraph.end);
+}
+
+/// The mouse or a touch, for picking menu entries drawn in bitmap text.
+#[derive(SystemParam)]
+pub struct Pointer<'w, 's> {
+    window: Query<'w, 's, &'static Window>,
+    camera:
+        Query<'w, 's, (&'static GlobalTransform, &'static OrthographicProjection), With<Camera2d>>,
+    mouse: Option<Res<'w, Input<MouseButton>>>,
+    touches: Option<Res<'w, Touches>>,
+}
+
+impl Pointer<'_, '_> {
+    /// Where the pointer is in the world and whether it was just pressed there, a new touch
+    /// before the mouse.
+    pub fn get(&self) -> Option<(Vec2, bool)> {
+        let window = self.window.get_single().ok()?;
+        let (camera, projection) = self.camera.get_single().ok()?;
+        let (position, pressed) = match self
+            .touches
+            .as_ref()
+            .and_then(|t| t.iter_just_pressed().next())
+        {
+            Some(touch) => (touch.position(), true),
+            None => (
+                window.cursor_position()?,
+                self.mouse
+                    .as_ref()
+                    .is_some_and(|mouse| mouse.just_pressed(MouseButton::Left)),
+            ),
+        };
+        let centered =
+            (position - Vec2::new(window.width(), window.height()) * 0.5) * Vec2::new(1.0, -1.0);
+        Some((
+            camera.translation().truncate() + centered * projection.scale,
+            pressed,
+        ))
+    }
+
+    /// The entry under the pointer, and whether it was just pressed. Only pass the entries on
+    /// screen.
+    pub fn pick<'a, T: 'a>(
+        &self,
+        entries: impl IntoIterator<Item = (T, &'a BitmapText, &'a GlobalTransform)>,
+    ) -> Option<(T, bool)> {
+        let (point, pressed) = self.get()?;
+        entries
+            .into_iter()
+            .find(|(_, text, transform)| text.contains(transform, point))
+            .map(|(entry, ..)| (entry, pressed))
+    }
+}
+
+/// A glyph sprite, `rest` is where [`render`] put it so it can be animated around that.
+#[derive(Component)]
+pub struct Glyph {
+    pub index: usize,
+    pub rest: Vec2,
+}
+
+pub fn load(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let texture_handle = asset_server.load("font.png");
+    let texture_atlas = TextureAtlas::from_grid(texture_handle, CELL, COLUMNS, ROWS, None, None);
+    commands.insert_resource(BitmapFont {
+        atlas: texture_atlases.add(texture_atlas),
+    });
+}
+
+/// Respawns the glyphs of every text that changed.
+pub fn render(
+    mut commands: Commands,
+    font: Res<BitmapFont>,
+    texts: Query<(Entity, &BitmapText, Option<&Children>), Changed<BitmapText>>,
+    glyphs: Query<(), With<Glyph>>,
+) {
+    for (entity, text, children) in &texts {
+        for child in children.into_iter().flatten() {
+            if glyphs.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        commands.entity(entity).with_children(|parent| {
+            for glyph in text.layout() {
+                // the glyph starts a pixel into its cell
+                let rest = glyph.position + Vec2::new(-1.0, 1.0);
+                parent.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: font.atlas.clone(),
+                        sprite: TextureAtlasSprite {
+                            index: glyph.atlas_index,
+                            color: glyph.color,
+                            anchor: Anchor::TopLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_translation(rest.extend(0.0)),
+                        ..default()
+                    },
+                    Glyph {
+                        index: glyph.index,
+                        rest,
+                    },
+                ));
+            }
+        });
+    }
+}
@@ -0,0 +1,279 @@
+//! Arena themes, listed in `assets/arena.themes.ron` so new ones only need sprites and an entry
+//! there.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Deserializer};
+
+use crate::{consts, settings::Settings, text};
+
+pub const THEMES_PATH: &str = "arena.themes.ron";
+/// How far apart in lightness text and the background have to be for the text to be read.
+const MIN_CONTRAST: f32 = 0.4;
+
+fn ink() -> Color {
+    text::INK
+}
+
+fn hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    Color::hex(&hex).map_err(|error| serde::de::Error::custom(format!("{hex}: {error:?}")))
+}
+
+/// A net drawn over the table, `sprite` is white and tinted with `color`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct NetStyle {
+    pub sprite: String,
+    #[serde(deserialize_with = "hex")]
+    pub color: Color,
+}
+
+/// Three stems played together and crossfaded by how intense the match is.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ThemeMusic {
+    pub stems: [String; 3],
+    /// Tempo of the stems, crossfades start on their beats.
+    pub bpm: f32,
+}
+
+/// Specks drifting over the arena, sizes and speeds are in table pixels.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Particles {
+    #[serde(deserialize_with = "hex")]
+    pub color: Color,
+    pub count: usize,
+    pub size: f32,
+    pub velocity: (f32, f32),
+    /// How far they sway from side to side.
+    pub sway: f32,
+    /// How many times a second they fade out and back in, 0 to stay lit.
+    pub twinkle: f32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub table: String,
+    #[serde(deserialize_with = "hex")]
+    pub background: Color,
+    /// Text over the background, the hud and menus, the palette's darkest color by default.
+    #[serde(default = "ink", deserialize_with = "hex")]
+    pub ink: Color,
+    /// Uses the net drawn on the table sprite when there's none.
+    #[serde(default)]
+    pub net: Option<NetStyle>,
+    #[serde(default)]
+    pub particles: Option<Particles>,
+    /// Takes the place of the three match stems, the menu track stays the same.
+    #[serde(default)]
+    pub music: Option<ThemeMusic>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: "lavender".into(),
+            table: "table.png".into(),
+            background: Color::hex("bdadf7").expect("a valid color"),
+            ink: text::INK,
+            net: None,
+            particles: None,
+            music: None,
+        }
+    }
+}
+
+impl Theme {
+    /// `color` for text over the background, or the ink if it would be hard to read.
+    pub fn readable(&self, color: Color) -> Color {
+        let lightness = |color: Color| color.as_hsla_f32()[2];
+        if (lightness(color) - lightness(self.background)).abs() < MIN_CONTRAST {
+            self.ink
+        } else {
+            color
+        }
+    }
+}
+
+#[derive(Deserialize, TypeUuid, TypePath, Clone, Debug, PartialEq)]
+#[uuid = "c3a4f7e2-6b1d-4e8a-9d35-7f0b2e9c4a16"]
+pub struct ThemeManifest {
+    pub themes: Vec<Theme>,
+}
+
+/// The themes from the manifest, only the default one until it's loaded.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Themes(pub Vec<Theme>);
+
+impl Default for Themes {
+    fn default() -> Self {
+        Self(vec![Theme::default()])
+    }
+}
+
+impl Themes {
+    /// The theme at `index`, the first one if there's no such theme.
+    pub fn get(&self, index: usize) -> &Theme {
+        self.0.get(index).unwrap_or(&self.0[0])
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|theme| theme.name.clone()).collect()
+    }
+
+    pub fn current(&self, settings: &Settings) -> &Theme {
+        self.get(settings.theme)
+    }
+}
+
+#[derive(Resource)]
+pub struct ThemesHandle(pub Handle<ThemeManifest>);
+
+#[derive(Default)]
+pub struct ThemeManifestLoader;
+
+impl AssetLoader for ThemeManifestLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let manifest: ThemeManifest = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(manifest));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["themes.ron"]
+    }
+}
+
+pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ThemesHandle(asset_server.load(THEMES_PATH)));
+}
+
+/// Takes the themes from the manifest whenever it's loaded or changed.
+pub fn update(
+    mut events: EventReader<AssetEvent<ThemeManifest>>,
+    assets: Res<Assets<ThemeManifest>>,
+    handle: Res<ThemesHandle>,
+    mut themes: ResMut<Themes>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                match assets.get(changed) {
+                    Some(manifest) if manifest.themes.is_empty() => {
+                        warn!("{THEMES_PATH} has no themes")
+                    }
+                    Some(manifest) => themes.0 = manifest.themes.clone(),
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn apply_background(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    clear_color: Option<ResMut<ClearColor>>,
+) {
+    let Some(mut clear_color) = clear_color else {
+        return;
+    };
+    if settings.is_changed() || themes.is_changed() {
+        clear_color.0 = themes.current(&settings).background;
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub index: usize,
+}
+
+/// Where particle `index` sits, as fractions of the window, evenly spread and the same every run.
+fn scatter(index: usize) -> Vec2 {
+    let golden = 0.618_034;
+    Vec2::new(
+        (index as f32 * golden).fract(),
+        (index as f32 * golden * golden + 0.5).fract(),
+    )
+}
+
+/// Respawns the theme's particles when it changes and drifts them around the window.
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    window: Query<&Window>,
+    mut applied: Local<Option<Option<Particles>>>,
+    mut particles: Query<(
+        Entity,
+        &Particle,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+) {
+    let theme = themes.current(&settings);
+    if applied.as_ref() != Some(&theme.particles) {
+        *applied = Some(theme.particles.clone());
+        for (entity, ..) in &particles {
+            commands.entity(entity).despawn();
+        }
+        for index in 0..theme
+            .particles
+            .as_ref()
+            .map_or(0, |particles| particles.count)
+        {
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                Particle { index },
+            ));
+        }
+        return;
+    }
+    let Some(style) = &theme.particles else {
+        return;
+    };
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let size = Vec2::new(window.width(), window.height());
+    let elapsed = time.elapsed_seconds();
+    let velocity = Vec2::new(style.velocity.0, style.velocity.1) * consts::SCALE;
+    for (_, particle, mut transform, mut sprite, mut visibility) in &mut particles {
+        let phase = particle.index as f32;
+        let start = scatter(particle.index) * size;
+        let drifted = start + velocity * elapsed;
+        let sway = (elapsed + phase).sin() * style.sway * consts::SCALE;
+        // wraps around so they keep coming in from the other side
+        let position = (drifted + Vec2::new(sway, 0.0)).rem_euclid(size) - size * 0.5;
+        transform.translation = position.extend(transform.translation.z);
+        let lit = if style.twinkle > 0.0 {
+            0.5 + 0.5 * (elapsed * style.twinkle * TAU + phase).sin()
+        } else {
+            1.0
+        };
+        sprite.color = style.color.with_a(style.color.a() * lit);
+        sprite.custom_size = Some(Vec2::splat(style.size * consts::SCALE));
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
@@ -0,0 +1,949 @@
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    audio_settings::AudioMenu,
+    ball::Ball,
+    bat::{self, Bat, Variant},
+    events::{MenuConfirmed, MenuMoved, Served},
+    highscores::{self, HighScores},
+    keymap,
+    players::{self, PlayerRow, PlayerSettings},
+    replay::{Playback, PlaybackUI, Replay},
+    settings::{Difficulty, GameMode, GameRng, MatchFormat, Settings},
+    state::{self, GameState, State},
+    text::{self, BitmapText, Pointer},
+    theme::Themes,
+    tournament::{self, Tournament},
+    tuning::GameTuning,
+};
+
+const HEADING_Y: f32 = 250.0;
+const ITEMS_Y: f32 = 150.0;
+/// Space from one menu entry to the next, a line of text and a font pixel.
+const ITEM_SPACING: f32 = (text::LINE_HEIGHT + 1) as f32 * text::PIXEL;
+/// Columns of the page text before it wraps.
+const BODY_COLUMNS: usize = 40;
+/// Menu entries other than the selected one are drawn in faded ink.
+const UNSELECTED_ALPHA: f32 = 0.5;
+const REPLAYS_SHOWN: usize = 10;
+const POINTS: [u32; 5] = [1, 3, 5, 11, 21];
+const WIN_BY: [u32; 2] = [1, 2];
+const BEST_OF: [u32; 4] = [1, 3, 5, 7];
+const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+const MODES: [GameMode; 3] = [GameMode::Versus, GameMode::Cpu, GameMode::Practice];
+const CREDITS: &str = "CREDITS
+game, art and sound by the bordtennis contributors
+sprites drawn in aseprite
+made with bevy";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TitleAction {
+    Versus,
+    Cpu,
+    Practice,
+    Tournament,
+    Players,
+    Replays,
+    HighScores,
+    Settings,
+    Credits,
+}
+
+impl TitleAction {
+    pub const ALL: [TitleAction; 9] = [
+        TitleAction::Versus,
+        TitleAction::Cpu,
+        TitleAction::Practice,
+        TitleAction::Tournament,
+        TitleAction::Players,
+        TitleAction::Replays,
+        TitleAction::HighScores,
+        TitleAction::Settings,
+        TitleAction::Credits,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TitleAction::Versus => "play versus",
+            TitleAction::Cpu => "play vs cpu",
+            TitleAction::Practice => "practice",
+            TitleAction::Tournament => "tournament",
+            TitleAction::Players => "players",
+            TitleAction::Replays => "replays",
+            TitleAction::HighScores => "high scores",
+            TitleAction::Settings => "settings",
+            TitleAction::Credits => "credits",
+        }
+    }
+
+    fn index(&self) -> usize {
+        TitleAction::ALL
+            .iter()
+            .position(|action| action == self)
+            .expect("every action is listed")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SetupRow {
+    LightName,
+    LightBat,
+    DarkName,
+    DarkBat,
+    Difficulty,
+    Points,
+    WinBy,
+    BestOf,
+    Theme,
+    Start,
+}
+
+impl SetupRow {
+    /// The line of the players page this row changes, for the rows about a player.
+    fn player_row(self) -> Option<PlayerRow> {
+        match self {
+            SetupRow::LightName => Some(PlayerRow::Name(Variant::Light)),
+            SetupRow::LightBat => Some(PlayerRow::Bat(Variant::Light)),
+            SetupRow::DarkName => Some(PlayerRow::Name(Variant::Dark)),
+            SetupRow::DarkBat => Some(PlayerRow::Bat(Variant::Dark)),
+            _ => None,
+        }
+    }
+}
+
+/// The match about to be played, copied into [`Settings`] on start. Names and colors are kept
+/// in [`PlayerSettings`] as they're picked, like on the players page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchSetup {
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    pub format: MatchFormat,
+    pub theme: usize,
+    /// Names of the themes to pick from, in the order of [`Themes`].
+    pub themes: Vec<String>,
+    /// The players of a tournament, every match of it is played like this one.
+    pub entrants: Option<[String; tournament::ENTRANTS]>,
+    pub row: usize,
+}
+
+impl MatchSetup {
+    pub fn new(mode: GameMode, settings: &Settings, themes: &Themes) -> Self {
+        Self {
+            mode,
+            difficulty: settings.difficulty,
+            format: settings.format,
+            // like Themes::get, an unknown theme is the first one
+            theme: if settings.theme < themes.0.len() {
+                settings.theme
+            } else {
+                0
+            },
+            themes: themes.names(),
+            entrants: None,
+            row: 0,
+        }
+    }
+
+    /// Versus matches between the `entrants`, who are named already.
+    pub fn tournament(
+        entrants: [String; tournament::ENTRANTS],
+        settings: &Settings,
+        themes: &Themes,
+    ) -> Self {
+        Self {
+            entrants: Some(entrants),
+            ..Self::new(GameMode::Versus, settings, themes)
+        }
+    }
+
+    /// Practice is against the wall, so there's no match to set up, and the cpu keeps its name.
+    pub fn rows(&self) -> Vec<SetupRow> {
+        if self.entrants.is_some() {
+            return vec![
+                SetupRow::Points,
+                SetupRow::WinBy,
+                SetupRow::BestOf,
+                SetupRow::Theme,
+                SetupRow::Start,
+            ];
+        }
+        match self.mode {
+            GameMode::Practice => vec![
+                SetupRow::LightName,
+                SetupRow::LightBat,
+                SetupRow::Theme,
+                SetupRow::Start,
+            ],
+            GameMode::Cpu => vec![
+                SetupRow::LightName,
+                SetupRow::LightBat,
+                SetupRow::DarkBat,
+                SetupRow::Difficulty,
+                SetupRow::Points,
+                SetupRow::WinBy,
+                SetupRow::BestOf,
+                SetupRow::Theme,
+                SetupRow::Start,
+            ],
+            GameMode::Versus => vec![
+                SetupRow::LightName,
+                SetupRow::LightBat,
+                SetupRow::DarkName,
+                SetupRow::DarkBat,
+                SetupRow::Points,
+                SetupRow::WinBy,
+                SetupRow::BestOf,
+                SetupRow::Theme,
+                SetupRow::Start,
+            ],
+        }
+    }
+
+    /// Steps the value in `row` through its choices, wrapping around.
+    pub fn adjust(&mut self, row: SetupRow, steps: isize) {
+        match row {
+            SetupRow::Difficulty => self.difficulty = cycle(&DIFFICULTIES, self.difficulty, steps),
+            SetupRow::Points => {
+                self.format.points_per_game = cycle(&POINTS, self.format.points_per_game, steps)
+            }
+            SetupRow::WinBy => self.format.win_by = cycle(&WIN_BY, self.format.win_by, steps),
+            SetupRow::BestOf => self.format.best_of = cycle(&BEST_OF, self.format.best_of, steps),
+            SetupRow::Theme => {
+                let count = self.themes.len() as isize;
+                self.theme = (self.theme as isize + steps).rem_euclid(count) as usize;
+            }
+            SetupRow::LightName
+            | SetupRow::LightBat
+            | SetupRow::DarkName
+            | SetupRow::DarkBat
+            | SetupRow::Start => {}
+        }
+    }
+
+    fn value(&self, row: SetupRow, settings: &Settings, players: &PlayerSettings) -> String {
+        match row {
+            SetupRow::LightName | SetupRow::LightBat | SetupRow::DarkName | SetupRow::DarkBat => {
+                players.value(settings, &row.player_row().expect("a row about a player"))
+            }
+            SetupRow::Difficulty => difficulty_label(self.difficulty).to_string(),
+            SetupRow::Points => self.format.points_per_game.to_string(),
+            SetupRow::WinBy => self.format.win_by.to_string(),
+            SetupRow::BestOf => self.format.best_of.to_string(),
+            SetupRow::Theme => self.themes[self.theme].clone(),
+            SetupRow::Start => String::new(),
+        }
+    }
+}
+
+/// The next choice after `current`, the first one if `current` isn't a choice.
+fn cycle<T: Copy + PartialEq>(choices: &[T], current: T, steps: isize) -> T {
+    let Some(index) = choices.iter().position(|choice| *choice == current) else {
+        return choices[0];
+    };
+    let index = (index as isize + steps).rem_euclid(choices.len() as isize);
+    choices[index as usize]
+}
+
+fn mode_label(mode: GameMode) -> &'static str {
+    match mode {
+        GameMode::Versus => "versus",
+        GameMode::Cpu => "vs cpu",
+        GameMode::Practice => "practice",
+    }
+}
+
+fn difficulty_label(difficulty: Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Easy => "easy",
+        Difficulty::Normal => "normal",
+        Difficulty::Hard => "hard",
+    }
+}
+
+fn row_label(row: SetupRow) -> &'static str {
+    match row {
+        SetupRow::LightName | SetupRow::LightBat | SetupRow::DarkName | SetupRow::DarkBat => {
+            row.player_row().expect("a row about a player").label()
+        }
+        SetupRow::Difficulty => "difficulty",
+        SetupRow::Points => "points",
+        SetupRow::WinBy => "win by",
+        SetupRow::BestOf => "best of",
+        SetupRow::Theme => "arena",
+        SetupRow::Start => "start",
+    }
+}
+
+/// The menu page shown over a new game, gone once a match or replay starts.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub enum Screen {
+    Title {
+        selected: usize,
+    },
+    Setup(MatchSetup),
+    /// Names and colors, changed in [`PlayerSettings`] as they're picked.
+    Players {
+        row: usize,
+    },
+    /// The names of the players of a tournament, `row` past the last name is the way on.
+    Tournament {
+        entrants: [String; tournament::ENTRANTS],
+        row: usize,
+    },
+    Replays {
+        files: Vec<PathBuf>,
+        selected: usize,
+    },
+    HighScores {
+        page: usize,
+    },
+    Credits,
+    Notice {
+        heading: &'static str,
+        message: &'static str,
+    },
+}
+
+impl Screen {
+    /// The main menu entry leading here, selected again when going back.
+    fn parent(&self) -> Option<TitleAction> {
+        match self {
+            Screen::Title { .. } => None,
+            Screen::Setup(setup) if setup.entrants.is_some() => Some(TitleAction::Tournament),
+            Screen::Setup(setup) => Some(match setup.mode {
+                GameMode::Versus => TitleAction::Versus,
+                GameMode::Cpu => TitleAction::Cpu,
+                GameMode::Practice => TitleAction::Practice,
+            }),
+            Screen::Players { .. } => Some(TitleAction::Players),
+            Screen::Tournament { .. } => Some(TitleAction::Tournament),
+            Screen::Replays { .. } => Some(TitleAction::Replays),
+            Screen::HighScores { .. } => Some(TitleAction::HighScores),
+            Screen::Credits => Some(TitleAction::Credits),
+            // only replays that fail to load show a notice
+            Screen::Notice { .. } => Some(TitleAction::Replays),
+        }
+    }
+
+    /// Whether a name is being typed, so letter keys shouldn't do anything else.
+    pub fn typing(&self) -> bool {
+        match self {
+            Screen::Players { row } => matches!(PlayerRow::ALL[*row], PlayerRow::Name(_)),
+            Screen::Tournament { row, .. } => *row < tournament::ENTRANTS,
+            Screen::Setup(setup) => matches!(
+                setup.rows()[setup.row].player_row(),
+                Some(PlayerRow::Name(_))
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// Run condition for the keys that would get in the way of typing a name.
+pub fn typing(screen: Option<Res<Screen>>) -> bool {
+    screen.is_some_and(|screen| screen.typing())
+}
+
+/// Every saved match in the replay directory, newest first.
+pub fn replay_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("replay-") && name.ends_with(".ron"))
+        })
+        .collect();
+    files.sort();
+    files.reverse();
+    files
+}
+
+/// A step through the menu, read from the keyboard, a gamepad, the mouse or touch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    Hover(usize),
+    Press(usize),
+    Type(char),
+    Erase,
+}
+
+#[derive(Component)]
+pub struct TitleUI;
+
+#[derive(Component)]
+pub struct TitleHeading;
+
+#[derive(Component)]
+pub struct TitleItem(usize);
+
+#[derive(Component)]
+pub struct TitleBody;
+
+/// Drawn over the game, in front of the court and its sprites.
+pub fn spawn(mut commands: Commands) {
+    commands
+        .spawn((
+            SpatialBundle {
+                transform: Transform::from_xyz(0.0, 0.0, 300.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TitleUI,
+        ))
+        .with_children(|title| {
+            title.spawn((
+                SpatialBundle::from_transform(
+                    Transform::from_scale(Vec3::splat(2.0 * text::PIXEL))
+                        .with_translation(Vec3::new(0.0, HEADING_Y, 0.0)),
+                ),
+                BitmapText::new("BORDTENNIS", text::INK),
+                TitleHeading,
+            ));
+            for (index, action) in TitleAction::ALL.iter().enumerate() {
+                title.spawn((
+                    SpatialBundle::from_transform(
+                        Transform::from_scale(Vec3::splat(text::PIXEL)).with_translation(
+                            Vec3::new(0.0, ITEMS_Y - index as f32 * ITEM_SPACING, 0.0),
+                        ),
+                    ),
+                    BitmapText::new(action.label(), text::INK).with_anchor(Anchor::TopCenter),
+                    TitleItem(index),
+                ));
+            }
+            title.spawn((
+                SpatialBundle::from_transform(
+                    Transform::from_scale(Vec3::splat(text::PIXEL))
+                        .with_translation(Vec3::new(0.0, ITEMS_Y, 0.0)),
+                ),
+                BitmapText::new("", text::INK)
+                    .with_anchor(Anchor::TopCenter)
+                    .with_max_columns(BODY_COLUMNS),
+                TitleBody,
+            ));
+        });
+}
+
+/// Brings the title screen up whenever the game is back at a new game.
+pub fn show(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    screen: Option<Res<Screen>>,
+    playback: Option<Res<Playback>>,
+    state: Query<&State>,
+) {
+    let new_game = matches!(state.single().game_state, GameState::NewGame);
+    if settings.title_screen && new_game && screen.is_none() && playback.is_none() {
+        commands.insert_resource(Screen::Title { selected: 0 });
+    } else if !new_game && screen.is_some() {
+        commands.remove_resource::<Screen>();
+    }
+}
+
+/// The menu step asked for this frame. Clears the keys, as letters typed into a name would
+/// otherwise pause or restart the game.
+pub fn navigate(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    gamepad: Res<Input<GamepadButton>>,
+    screen: Res<Screen>,
+    pointer: Pointer,
+    items: Query<(&TitleItem, &BitmapText, &GlobalTransform)>,
+    mut hovered: Local<Option<usize>>,
+) -> Option<MenuInput> {
+    // the entries are only on the title page
+    let picked = match *screen {
+        Screen::Title { .. } => pointer.pick(
+            items
+                .iter()
+                .map(|(item, text, transform)| (item.0, text, transform)),
+        ),
+        _ => None,
+    };
+    let pressed = |key: KeyCode, button: GamepadButtonType| {
+        keys.just_pressed(key) || keymap::gamepad_pressed(&gamepad, button)
+    };
+    let input = if pressed(keymap::menu_up(), GamepadButtonType::DPadUp) {
+        Some(MenuInput::Up)
+    } else if pressed(keymap::menu_down(), GamepadButtonType::DPadDown) {
+        Some(MenuInput::Down)
+    } else if pressed(keymap::cursor_left(), GamepadButtonType::DPadLeft) {
+        Some(MenuInput::Left)
+    } else if pressed(keymap::cursor_right(), GamepadButtonType::DPadRight) {
+        Some(MenuInput::Right)
+    } else if pressed(keymap::confirm(), GamepadButtonType::South) {
+        Some(MenuInput::Confirm)
+    } else if pressed(keymap::menu_close(), GamepadButtonType::East) {
+        Some(MenuInput::Back)
+    } else if keys.just_pressed(KeyCode::Back) {
+        Some(MenuInput::Erase)
+    } else if let Some(character) = characters
+        .iter()
+        .map(|event| event.char)
+        .find(char::is_ascii_alphanumeric)
+    {
+        // the rest are typed on the next frames
+        Some(MenuInput::Type(character))
+    } else {
+        match picked {
+            Some((index, true)) => Some(MenuInput::Press(index)),
+            // only a pointer moving onto another entry selects it
+            Some((index, false)) if *hovered != Some(index) => Some(MenuInput::Hover(index)),
+            _ => None,
+        }
+    };
+    *hovered = picked.map(|(index, _)| index);
+    keys.reset_all();
+    input
+}
+
+/// Moves between the pages, starting a match or replay when one is picked.
+#[allow(clippy::too_many_arguments)]
+pub fn choose(
+    In(input): In<Option<MenuInput>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tuning: Res<GameTuning>,
+    mut settings: ResMut<Settings>,
+    mut rng: ResMut<GameRng>,
+    mut screen: ResMut<Screen>,
+    mut players: ResMut<PlayerSettings>,
+    themes: Res<Themes>,
+    mut state: Query<&mut State>,
+    mut ball: Query<&mut Ball>,
+    mut bats: Query<&mut Bat>,
+    bat_entities: Query<Entity, With<Bat>>,
+    mut moved: EventWriter<MenuMoved>,
+    mut confirmed: EventWriter<MenuConfirmed>,
+    mut served: EventWriter<Served>,
+) {
+    let Some(input) = input else {
+        return;
+    };
+    let mut state = state.single_mut();
+    if input == MenuInput::Back {
+        if let Some(parent) = screen.parent() {
+            *screen = Screen::Title {
+                selected: parent.index(),
+            };
+            confirmed.send(MenuConfirmed);
+        }
+        return;
+    }
+    let before = screen.clone();
+    match &mut *screen {
+        Screen::Title { selected } => {
+            let last = TitleAction::ALL.len() - 1;
+            let action = match input {
+                MenuInput::Up => {
+                    *selected = selected.saturating_sub(1);
+                    None
+                }
+                MenuInput::Down => {
+                    *selected = (*selected + 1).min(last);
+                    None
+                }
+                MenuInput::Hover(index) => {
+                    *selected = index;
+                    None
+                }
+                MenuInput::Press(index) => Some(TitleAction::ALL[index]),
+                MenuInput::Confirm => Some(TitleAction::ALL[*selected]),
+                MenuInput::Left
+                | MenuInput::Right
+                | MenuInput::Back
+                | MenuInput::Type(_)
+                | MenuInput::Erase => None,
+            };
+            if let Some(action) = action {
+                confirmed.send(MenuConfirmed);
+                match action {
+                    TitleAction::Versus => {
+                        *screen =
+                            Screen::Setup(MatchSetup::new(GameMode::Versus, &settings, &themes))
+                    }
+                    TitleAction::Cpu => {
+                        *screen = Screen::Setup(MatchSetup::new(GameMode::Cpu, &settings, &themes))
+                    }
+                    TitleAction::Practice => {
+                        *screen =
+                            Screen::Setup(MatchSetup::new(GameMode::Practice, &settings, &themes))
+                    }
+                    TitleAction::Tournament => {
+                        *screen = Screen::Tournament {
+                            entrants: Default::default(),
+                            row: 0,
+                        }
+                    }
+                    TitleAction::Players => *screen = Screen::Players { row: 0 },
+                    TitleAction::Replays => {
+                        *screen = Screen::Replays {
+                            files: settings
+                                .replay_dir
+                                .as_deref()
+                                .map(replay_files)
+                                .unwrap_or_default(),
+                            selected: 0,
+                        }
+                    }
+                    TitleAction::HighScores => *screen = Screen::HighScores { page: 0 },
+                    TitleAction::Settings => commands.init_resource::<AudioMenu>(),
+                    TitleAction::Credits => *screen = Screen::Credits,
+                }
+                return;
+            }
+        }
+        Screen::Setup(setup) => {
+            let rows = setup.rows();
+            let row = rows[setup.row];
+            if let Some(line) = row.player_row() {
+                let before = players.clone();
+                edit_player(&mut players, &line, input);
+                if *players != before {
+                    players::save(&settings, &players);
+                    moved.send(MenuMoved);
+                }
+            }
+            match input {
+                MenuInput::Up => setup.row = setup.row.saturating_sub(1),
+                MenuInput::Down => setup.row = (setup.row + 1).min(rows.len() - 1),
+                MenuInput::Confirm if row.player_row().is_some() => {
+                    setup.row = (setup.row + 1).min(rows.len() - 1)
+                }
+                MenuInput::Left => setup.adjust(row, -1),
+                MenuInput::Right => setup.adjust(row, 1),
+                MenuInput::Confirm if row == SetupRow::Start => {
+                    settings.mode = setup.mode;
+                    settings.difficulty = setup.difficulty;
+                    settings.format = setup.format;
+                    settings.theme = setup.theme;
+                    for entity in &bat_entities {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                    bat::spawn_players(&mut commands, &asset_server, &tuning, &settings, false);
+                    state::reset_rally(&tuning, &state.server, &mut ball, &mut bats);
+                    if let Some(entrants) = &setup.entrants {
+                        let tournament = Tournament::new(entrants.clone(), &players);
+                        tournament.apply(&mut players);
+                        commands.insert_resource(tournament);
+                    }
+                    if state.start_after(state::countdown_seconds(&settings, &tuning)) {
+                        served.send(Served {
+                            by: state.server.clone(),
+                        });
+                    }
+                    commands.remove_resource::<Screen>();
+                    confirmed.send(MenuConfirmed);
+                    return;
+                }
+                MenuInput::Confirm => setup.adjust(row, 1),
+                MenuInput::Hover(_)
+                | MenuInput::Press(_)
+                | MenuInput::Back
+                | MenuInput::Type(_)
+                | MenuInput::Erase => {}
+            }
+        }
+        Screen::Players { row } => {
+            let line = &PlayerRow::ALL[*row];
+            let before = players.clone();
+            match input {
+                MenuInput::Up => *row = row.saturating_sub(1),
+                MenuInput::Down | MenuInput::Confirm => {
+                    *row = (*row + 1).min(PlayerRow::ALL.len() - 1)
+                }
+                _ => edit_player(&mut players, line, input),
+            }
+            if *players != before {
+                players::save(&settings, &players);
+                moved.send(MenuMoved);
+            }
+        }
+        Screen::Tournament { entrants, row } => match input {
+            MenuInput::Up => *row = row.saturating_sub(1),
+            MenuInput::Confirm if *row == tournament::ENTRANTS => {
+                *screen =
+                    Screen::Setup(MatchSetup::tournament(entrants.clone(), &settings, &themes));
+                confirmed.send(MenuConfirmed);
+                return;
+            }
+            MenuInput::Down | MenuInput::Confirm => *row = (*row + 1).min(tournament::ENTRANTS),
+            MenuInput::Type(character) if *row < tournament::ENTRANTS => {
+                players::type_into(&mut entrants[*row], character)
+            }
+            MenuInput::Erase if *row < tournament::ENTRANTS => {
+                entrants[*row].pop();
+            }
+            _ => {}
+        },
+        Screen::Replays { files, selected } => match input {
+            MenuInput::Up => *selected = selected.saturating_sub(1),
+            MenuInput::Down => *selected = (*selected + 1).min(files.len().saturating_sub(1)),
+            MenuInput::Confirm if !files.is_empty() => {
+                confirmed.send(MenuConfirmed);
+                match Replay::load(&files[*selected]) {
+                    Ok(replay) => {
+                        settings.mode = replay.mode;
+                        settings.difficulty = replay.difficulty;
+                        settings.format = replay.format;
+                        *rng = GameRng::new(Some(replay.seed));
+                        for entity in &bat_entities {
+                            commands.entity(entity).despawn_recursive();
+                        }
+                        bat::spawn_players(
+                            &mut commands,
+                            &asset_server,
+                            &replay.tuning,
+                            &settings,
+                            true,
+                        );
+                        state::reset_rally(&replay.tuning, &state.server, &mut ball, &mut bats);
+                        commands.insert_resource(Playback::new(replay));
+                        commands.remove_resource::<Screen>();
+                    }
+                    Err(error) => {
+                        warn!("{error}");
+                        *screen = Screen::Notice {
+                            heading: "REPLAYS",
+                            message: "could not load that replay",
+                        };
+                    }
+                }
+                return;
+            }
+            _ => {}
+        },
+        Screen::HighScores { page } => {
+            let pages = (MODES.len() * DIFFICULTIES.len()) as isize;
+            match input {
+                MenuInput::Left => *page = (*page as isize - 1).rem_euclid(pages) as usize,
+                MenuInput::Right => *page = (*page as isize + 1).rem_euclid(pages) as usize,
+                _ => {}
+            }
+        }
+        Screen::Credits | Screen::Notice { .. } => {
+            if input == MenuInput::Confirm {
+                let parent = screen.parent().expect("pages have a parent");
+                *screen = Screen::Title {
+                    selected: parent.index(),
+                };
+                confirmed.send(MenuConfirmed);
+            }
+            return;
+        }
+    }
+    if *screen != before {
+        moved.send(MenuMoved);
+    }
+}
+
+/// Changes a name or color the same way on the players page and the match setup.
+fn edit_player(players: &mut PlayerSettings, line: &PlayerRow, input: MenuInput) {
+    match (input, line) {
+        (MenuInput::Left, _) => players.adjust(line, -1),
+        (MenuInput::Right, _) => players.adjust(line, 1),
+        (MenuInput::Type(character), PlayerRow::Name(variant)) => {
+            players.type_character(variant, character)
+        }
+        (MenuInput::Erase, PlayerRow::Name(variant)) => players.erase(variant),
+        _ => {}
+    }
+}
+
+/// `Esc` stops a replay picked from the menu and goes back to the title screen.
+#[allow(clippy::too_many_arguments)]
+pub fn leave_replay(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    tuning: Res<GameTuning>,
+    settings: Res<Settings>,
+    mut time: ResMut<Time>,
+    mut state: Query<&mut State>,
+    mut ball: Query<&mut Ball>,
+    mut bats: Query<&mut Bat>,
+    bat_entities: Query<Entity, With<Bat>>,
+    mut hud: Query<&mut BitmapText, With<PlaybackUI>>,
+) {
+    if !settings.title_screen || !keys.just_pressed(keymap::menu_close()) {
+        return;
+    }
+    commands.remove_resource::<Playback>();
+    time.unpause();
+    time.set_relative_speed(1.0);
+    hud.single_mut().sections.clear();
+    for entity in &bat_entities {
+        commands.entity(entity).despawn_recursive();
+    }
+    bat::spawn_players(&mut commands, &asset_server, &tuning, &settings, false);
+    let mut state = state.single_mut();
+    state.new_game();
+    state::reset_rally(&tuning, &state.server, &mut ball, &mut bats);
+}
+
+fn body_text(
+    screen: &Screen,
+    scores: &HighScores,
+    settings: &Settings,
+    players: &PlayerSettings,
+) -> String {
+    let mut text = String::new();
+    match screen {
+        Screen::Title { .. } => {}
+        Screen::Setup(setup) => {
+            match setup.entrants {
+                Some(_) => text.push_str("TOURNAMENT"),
+                None => text.push_str(&mode_label(setup.mode).to_uppercase()),
+            }
+            for (index, row) in setup.rows().into_iter().enumerate() {
+                let cursor = if index == setup.row { '>' } else { ' ' };
+                let value = setup.value(row, settings, players);
+                // a cursor after the name being typed
+                let value = if index == setup.row && screen.typing() {
+                    format!("{value}_")
+                } else {
+                    value
+                };
+                write!(text, "\n{cursor} {:<10} {value:>7}", row_label(row))
+                    .expect("writing to a string");
+            }
+        }
+        Screen::Players { row } => {
+            text.push_str("PLAYERS");
+            for (index, line) in PlayerRow::ALL.iter().enumerate() {
+                let cursor = if index == *row { '>' } else { ' ' };
+                let value = players.value(settings, line);
+                // a cursor after the name being typed
+                let value = if index == *row && screen.typing() {
+                    format!("{value}_")
+                } else {
+                    value
+                };
+                write!(text, "\n{cursor} {:<10} {value:>7}", line.label())
+                    .expect("writing to a string");
+            }
+        }
+        Screen::Tournament { entrants, row } => {
+            text.push_str("TOURNAMENT");
+            for (index, name) in entrants.iter().enumerate() {
+                let cursor = if index == *row { '>' } else { ' ' };
+                // a cursor after the name being typed, the default name until there is one
+                let value = match (index == *row, name.is_empty()) {
+                    (true, _) => format!("{name}_"),
+                    (false, true) => format!("P{}", index + 1),
+                    (false, false) => name.clone(),
+                };
+                write!(
+                    text,
+                    "\n{cursor} {:<10} {value:>7}",
+                    format!("player {}", index + 1)
+                )
+                .expect("writing to a string");
+            }
+            let cursor = if *row == tournament::ENTRANTS {
+                '>'
+            } else {
+                ' '
+            };
+            write!(text, "\n{cursor} next").expect("writing to a string");
+        }
+        Screen::Replays { files, selected } => {
+            text.push_str("REPLAYS");
+            if files.is_empty() {
+                text.push_str("\nno replays yet");
+            }
+            let first = selected.saturating_sub(REPLAYS_SHOWN - 1);
+            for (index, path) in files.iter().enumerate().skip(first).take(REPLAYS_SHOWN) {
+                let cursor = if index == *selected { '>' } else { ' ' };
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                write!(text, "\n{cursor} {name}").expect("writing to a string");
+            }
+        }
+        Screen::HighScores { page } => {
+            let mode = MODES[page / DIFFICULTIES.len()];
+            let difficulty = DIFFICULTIES[page % DIFFICULTIES.len()];
+            text.push_str(&highscores::table_text(scores, mode, difficulty));
+            write!(
+                text,
+                "\n< {} {} >",
+                mode_label(mode),
+                difficulty_label(difficulty)
+            )
+            .expect("writing to a string");
+        }
+        Screen::Credits => text.push_str(CREDITS),
+        Screen::Notice { heading, message } => {
+            write!(text, "{heading}\n{message}").expect("writing to a string")
+        }
+    }
+    text
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_ui(
+    screen: Option<Res<Screen>>,
+    audio_menu: Option<Res<AudioMenu>>,
+    scores: Res<HighScores>,
+    settings: Res<Settings>,
+    players: Res<PlayerSettings>,
+    themes: Res<Themes>,
+    mut root: Query<&mut Visibility, With<TitleUI>>,
+    mut items: Query<(&TitleItem, &mut BitmapText, &mut Visibility), Without<TitleUI>>,
+    mut body: Query<&mut BitmapText, (With<TitleBody>, Without<TitleItem>)>,
+    mut heading: Query<
+        &mut BitmapText,
+        (With<TitleHeading>, Without<TitleBody>, Without<TitleItem>),
+    >,
+) {
+    let mut visibility = root.single_mut();
+    let Some(screen) = screen.filter(|_| audio_menu.is_none()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+    let selected = match *screen {
+        Screen::Title { selected } => Some(selected),
+        _ => None,
+    };
+    let ink = themes.current(&settings).ink;
+    for (item, mut text, mut visibility) in &mut items {
+        *visibility = if selected.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        let color = if Some(item.0) == selected {
+            ink
+        } else {
+            ink.with_a(UNSELECTED_ALPHA)
+        };
+        if text.sections[0].color != color {
+            text.sections[0].color = color;
+        }
+    }
+    let mut heading = heading.single_mut();
+    if heading.sections[0].color != ink {
+        heading.sections[0].color = ink;
+    }
+    let value = body_text(&screen, &scores, &settings, &players);
+    let mut body = body.single_mut();
+    if body.sections[0].value != value || body.sections[0].color != ink {
+        body.sections[0].value = value;
+        body.sections[0].color = ink;
+    }
+}
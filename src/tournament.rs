@@ -0,0 +1,200 @@
+//! A knockout for four named players, played as versus matches one after the other: the two
+//! semifinals, then the final between their winners.
+
+use std::fmt::Write;
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    ball::Ball,
+    bat::{Bat, Variant},
+    instant_replay::InstantReplay,
+    players::PlayerSettings,
+    settings::Settings,
+    state::{self, GameState, State},
+    text::{self, BitmapText},
+    theme::Themes,
+    tuning::GameTuning,
+};
+
+pub const ENTRANTS: usize = 4;
+const ROUNDS: [&str; 3] = ["semifinal", "semifinal", "final"];
+/// Below the new game and winner text.
+const BRACKET_Y: f32 = -120.0;
+
+/// The bracket being played, from the start of the first semifinal until the game is back at a
+/// new game.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Tournament {
+    /// Names as typed, empty for the default one.
+    pub entrants: [String; ENTRANTS],
+    /// Who won each match so far, as indexes into `entrants`.
+    pub winners: Vec<usize>,
+    /// Whether the last match is over and the next one hasn't started yet.
+    decided: bool,
+    /// The names from the players page, put back once the tournament is over.
+    saved: [String; 2],
+}
+
+impl Tournament {
+    pub fn new(entrants: [String; ENTRANTS], players: &PlayerSettings) -> Self {
+        Self {
+            entrants,
+            winners: Vec::new(),
+            decided: false,
+            saved: [players.light.name.clone(), players.dark.name.clone()],
+        }
+    }
+
+    pub fn name(&self, index: usize) -> String {
+        match self.entrants[index].as_str() {
+            "" => format!("P{}", index + 1),
+            name => name.to_string(),
+        }
+    }
+
+    /// Who plays light and dark in `round`, unknown for the final until the semifinals are won.
+    fn pairing(&self, round: usize) -> [Option<usize>; 2] {
+        match round {
+            0 => [Some(0), Some(1)],
+            1 => [Some(2), Some(3)],
+            _ => [self.winners.first().copied(), self.winners.get(1).copied()],
+        }
+    }
+
+    /// The light and dark entrants of the match up next, none once there's a champion.
+    pub fn next_match(&self) -> Option<[usize; 2]> {
+        if self.winners.len() >= ROUNDS.len() {
+            return None;
+        }
+        match self.pairing(self.winners.len()) {
+            [Some(light), Some(dark)] => Some([light, dark]),
+            _ => None,
+        }
+    }
+
+    pub fn champion(&self) -> Option<String> {
+        (self.winners.len() == ROUNDS.len()).then(|| self.name(self.winners[ROUNDS.len() - 1]))
+    }
+
+    /// Puts the names of the next match's entrants on the bats.
+    pub fn apply(&self, players: &mut PlayerSettings) {
+        if let Some([light, dark]) = self.next_match() {
+            players.light.name = self.name(light);
+            players.dark.name = self.name(dark);
+        }
+    }
+
+    fn restore(&self, players: &mut PlayerSettings) {
+        players.light.name = self.saved[0].clone();
+        players.dark.name = self.saved[1].clone();
+    }
+
+    /// Moves `winner` of the match just played on to the next round.
+    pub fn record(&mut self, winner: &Variant) {
+        if let Some([light, dark]) = self.next_match() {
+            self.winners.push(match winner {
+                Variant::Light => light,
+                Variant::Dark => dark,
+            });
+        }
+    }
+
+    /// Every match with who won it, and the champion once there is one.
+    pub fn bracket_text(&self) -> String {
+        let mut text = "TOURNAMENT".to_string();
+        for (round, label) in ROUNDS.iter().enumerate() {
+            let [light, dark] = self.pairing(round).map(|entrant| match entrant {
+                Some(index) => self.name(index),
+                None => "?".to_string(),
+            });
+            write!(text, "\n{label:<9} {light:>6} v {dark:<6}").expect("writing to a string");
+            if let Some(winner) = self.winners.get(round) {
+                write!(text, " > {}", self.name(*winner)).expect("writing to a string");
+            }
+        }
+        if let Some(champion) = self.champion() {
+            write!(text, "\nCHAMPION {champion}").expect("writing to a string");
+        }
+        text
+    }
+}
+
+pub fn spawn(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(text::SMALL_PIXEL))
+                .with_translation(Vec3::new(0.0, BRACKET_Y, 200.0)),
+        ),
+        bracket_bitmap(String::new(), text::INK),
+        TournamentUI,
+    ));
+}
+
+#[derive(Component)]
+pub struct TournamentUI;
+
+fn bracket_bitmap(value: String, ink: Color) -> BitmapText {
+    BitmapText::new(value, ink).with_anchor(Anchor::TopCenter)
+}
+
+/// Moves the winner of every match on, puts the next two entrants on the bats once the players
+/// start again, and goes back to a new game after the final. Over when the game is back at a new
+/// game, from the pause menu, a restart or the final.
+pub fn update(
+    mut commands: Commands,
+    tuning: Res<GameTuning>,
+    mut tournament: ResMut<Tournament>,
+    mut players: ResMut<PlayerSettings>,
+    mut state: Query<&mut State>,
+    mut ball: Query<&mut Ball>,
+    mut bats: Query<&mut Bat>,
+) {
+    let mut state = state.single_mut();
+    match &state.game_state {
+        GameState::NewGame => {
+            tournament.restore(&mut players);
+            commands.remove_resource::<Tournament>();
+        }
+        GameState::Winner(winner) if state.match_over && !tournament.decided => {
+            let winner = winner.clone();
+            tournament.record(&winner);
+            tournament.decided = true;
+        }
+        GameState::Winner(_) if state.match_over => {}
+        _ if tournament.decided => {
+            tournament.decided = false;
+            if tournament.next_match().is_some() {
+                tournament.apply(&mut players);
+            } else {
+                state.new_game();
+                state::reset_rally(&tuning, &state.server, &mut ball, &mut bats);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Shows the bracket between matches.
+pub fn update_ui(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    tournament: Option<Res<Tournament>>,
+    instant_replay: Option<Res<InstantReplay>>,
+    state: Query<&State>,
+    mut ui: Query<&mut BitmapText, With<TournamentUI>>,
+) {
+    let state = state.single();
+    let value = match tournament {
+        Some(tournament)
+            if state.match_over
+                && matches!(state.game_state, GameState::Winner(_))
+                && instant_replay.is_none() =>
+        {
+            tournament.bracket_text()
+        }
+        _ => String::new(),
+    };
+    ui.single_mut()
+        .set_if_neq(bracket_bitmap(value, themes.current(&settings).ink));
+}
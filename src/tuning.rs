@@ -0,0 +1,140 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+pub const TUNING_PATH: &str = "game.tuning.ron";
+
+#[derive(Resource, Serialize, Deserialize, TypeUuid, Reflect, Clone, Debug, PartialEq)]
+#[uuid = "5b0c5a8e-3f1d-4c9a-9f57-1a4c1e1f2d6b"]
+// replays and tuning files from before a value was added fall back to its default
+#[serde(default)]
+pub struct GameTuning {
+    pub move_speed: f32,
+    pub run_speed: f32,
+    pub swing_cooldown: f32,
+    pub hit_radius: f32,
+    pub hit_x_multiplier: f32,
+    pub hit_y_distance_factor: f32,
+    pub hit_y_scale_min: f32,
+    pub hit_y_scale_max: f32,
+    pub max_ball_speed: f32,
+    pub serve_speed: f32,
+    pub wall_return_scale: f32,
+    /// Rally heat gained with every hit, the heat meter is full at 1.
+    pub heat_per_hit: f32,
+    /// How much faster than `max_ball_speed` the ball may go on a full heat meter.
+    pub heat_max_speed_scale: f32,
+    /// Added to `hit_y_scale_max` for every heat threshold passed.
+    pub heat_smash_bonus: f32,
+    /// Seconds counted down before each serve and after unpausing.
+    pub countdown: f32,
+}
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        let move_speed = 24.0;
+        Self {
+            move_speed,
+            run_speed: move_speed * 2.5,
+            swing_cooldown: 0.1,
+            hit_radius: 3.95,
+            hit_x_multiplier: 4.0,
+            hit_y_distance_factor: 0.4,
+            hit_y_scale_min: 0.9,
+            hit_y_scale_max: 1.25,
+            max_ball_speed: 64.0,
+            serve_speed: 0.5,
+            wall_return_scale: 1.25,
+            heat_per_hit: 0.08,
+            heat_max_speed_scale: 1.5,
+            heat_smash_bonus: 0.15,
+            countdown: 3.0,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct TuningHandle(pub Handle<GameTuning>);
+
+#[derive(Event)]
+pub struct TuningLoadFailed(pub String);
+
+#[derive(Resource, Clone, Default)]
+pub struct TuningErrors(Arc<Mutex<Vec<String>>>);
+
+impl TuningErrors {
+    fn push(&self, message: String) {
+        self.0.lock().expect("tuning errors lock").push(message);
+    }
+
+    fn drain(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().expect("tuning errors lock"))
+    }
+}
+
+pub struct GameTuningLoader {
+    errors: TuningErrors,
+}
+
+impl GameTuningLoader {
+    pub fn new(errors: TuningErrors) -> Self {
+        Self { errors }
+    }
+}
+
+impl AssetLoader for GameTuningLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let tuning: GameTuning = ron::de::from_bytes(bytes).map_err(|error| {
+                self.errors
+                    .push(format!("{}: {}", load_context.path().display(), error));
+                error
+            })?;
+            load_context.set_default_asset(LoadedAsset::new(tuning));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+pub fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+pub fn update(
+    mut events: EventReader<AssetEvent<GameTuning>>,
+    mut failed: EventWriter<TuningLoadFailed>,
+    assets: Res<Assets<GameTuning>>,
+    handle: Res<TuningHandle>,
+    errors: Res<TuningErrors>,
+    mut tuning: ResMut<GameTuning>,
+) {
+    for message in errors.drain() {
+        failed.send(TuningLoadFailed(message));
+    }
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                if let Some(loaded) = assets.get(changed) {
+                    *tuning = loaded.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,51 +1,116 @@
 use bevy::{prelude::*, sprite::Anchor, window::WindowResized};
 
 use crate::{
+    bat::Variant,
     consts,
+    palette::{PaletteSwap, Part},
+    players::PlayerSettings,
+    settings::Settings,
     state::{GameState, State},
+    text::{self, BitmapSection, BitmapText, Glyph},
+    theme::Themes,
+    title::Screen,
 };
 
 #[derive(Component)]
 pub struct ControlsUI;
 
 #[derive(Component)]
-pub struct ScoreUI(usize);
+pub struct ScoreUI;
 
 #[derive(Component)]
-pub struct TimerUI(usize);
+pub struct TimerUI;
 
-fn timer_text_x_position(digit: usize, max_digits: usize, window_width: f32) -> f32 {
-    let half = (max_digits / 2) as isize;
-    let text_width = 4.0 * 0.5 * consts::SCALE;
-    let text_padding = 0.5 * consts::SCALE;
+/// One player's side of the scoreboard, Light's at the bottom and Dark's at the top.
+#[derive(Component)]
+pub struct ScoreboardUI(pub Variant);
 
-    let difference: isize = half - digit as isize;
+#[derive(Component)]
+pub struct ScoreboardText(pub Variant);
 
-    text_width * (difference as f32) + text_padding * (difference as f32) + window_width * 0.5
-        - text_width * max_digits as f32
-}
+/// Shown on the side of whoever serves next.
+#[derive(Component)]
+pub struct ServeIcon(pub Variant);
 
-fn score_text_x_position(digit: usize, max_digits: usize) -> f32 {
-    let half = (max_digits / 2) as isize;
-    let text_width = 4.0 * 0.5 * consts::SCALE;
-    let text_padding = 0.5 * consts::SCALE;
+fn scoreboard_position(variant: &Variant, window_width: f32, window_height: f32) -> Vec2 {
+    let x = window_width * -0.5 + text::PIXEL;
+    match variant {
+        Variant::Light => Vec2::new(
+            x,
+            window_height * -0.5 + (text::LINE_HEIGHT as f32) * text::PIXEL,
+        ),
+        Variant::Dark => Vec2::new(x, window_height * 0.5 - text::PIXEL),
+    }
+}
 
-    let difference: isize = half - digit as isize;
+/// Name and points, with the games won once the match has more than one game.
+fn scoreboard_text(
+    name: &str,
+    points: u32,
+    games: Option<u32>,
+    color: Color,
+    faded: Color,
+) -> BitmapText {
+    let mut sections = vec![BitmapSection {
+        value: format!("{name:<6}{points:>3}"),
+        color,
+    }];
+    if let Some(games) = games {
+        sections.push(BitmapSection {
+            value: format!("  games {games}"),
+            color: faded,
+        });
+    }
+    BitmapText {
+        sections,
+        anchor: Anchor::TopLeft,
+        ..default()
+    }
+}
 
-    text_width * (difference as f32) + text_padding * (difference as f32)
+fn timer_text_x_position(window_width: f32) -> f32 {
+    window_width * 0.5 - text::PIXEL
 }
 
 fn top_text_y_position(window_height: f32) -> f32 {
-    let height = 6.0 * 0.5 * consts::SCALE;
-    let text_padding = 0.5 * consts::SCALE;
-
-    window_height * 0.5 - height - text_padding
+    window_height * 0.5 - 4.0 * text::PIXEL
 }
 
 const SCORE_DIGITS: usize = 7;
 const TIMER_DIGITS: usize = 3;
 const TIMER_SUB_DIGITS: usize = 1;
 
+fn score_text(score: &str, ink: Color) -> BitmapText {
+    BitmapText::new(score, ink)
+        .with_alignment(TextAlignment::Center)
+        .with_anchor(Anchor::TopCenter)
+}
+
+fn timer_text(seconds: f32, ink: Color, faded: Color) -> BitmapText {
+    let timer = format!(
+        "{:0width$.precision$}",
+        seconds,
+        width = TIMER_DIGITS + TIMER_SUB_DIGITS + 1,
+        precision = TIMER_SUB_DIGITS
+    );
+    let (whole, sub) = timer.split_at(timer.len() - TIMER_SUB_DIGITS - 1);
+    BitmapText {
+        sections: vec![
+            BitmapSection {
+                value: whole.to_string(),
+                color: ink,
+            },
+            BitmapSection {
+                value: sub.to_string(),
+                color: faded,
+            },
+        ],
+        ..default()
+    }
+    .with_alignment(TextAlignment::Right)
+    .with_anchor(Anchor::TopRight)
+}
+
 pub fn spawn(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -75,132 +140,174 @@ pub fn spawn(
             ControlsUI,
         ));
     }
-    {
-        let texture_handle = asset_server.load("score.png");
-        let texture_atlas =
-            TextureAtlas::from_grid(texture_handle, Vec2::new(6.0, 8.0), 8, 3, None, None);
-        let texture_atlas = texture_atlases.add(texture_atlas);
-
-        for digit in 0..SCORE_DIGITS {
-            let texture_atlas = texture_atlas.clone();
-            commands.spawn((
-                SpriteSheetBundle {
-                    texture_atlas,
-                    transform: Transform::from_scale(Vec3::splat(0.5 * consts::SCALE))
-                        .with_translation(Vec3::new(
-                            score_text_x_position(digit, SCORE_DIGITS),
-                            top_text_y_position(window.height()),
-                            100.0,
-                        )),
-                    sprite: TextureAtlasSprite {
-                        index: 0,
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(text::PIXEL)).with_translation(Vec3::new(
+                0.0,
+                top_text_y_position(window.height()),
+                100.0,
+            )),
+        ),
+        score_text("", text::INK),
+        ScoreUI,
+    ));
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_scale(Vec3::splat(text::PIXEL)).with_translation(Vec3::new(
+                timer_text_x_position(window.width()),
+                top_text_y_position(window.height()),
+                100.0,
+            )),
+        ),
+        timer_text(0.0, text::INK, text::FADED),
+        TimerUI,
+    ));
+    for variant in [Variant::Light, Variant::Dark] {
+        let position = scoreboard_position(&variant, window.width(), window.height());
+        commands
+            .spawn((
+                SpatialBundle::from_transform(
+                    Transform::from_scale(Vec3::splat(text::PIXEL))
+                        .with_translation(position.extend(100.0)),
+                ),
+                ScoreboardUI(variant.clone()),
+            ))
+            .with_children(|side| {
+                let texture = asset_server.load("ball.png");
+                side.spawn((
+                    SpriteBundle {
+                        texture: texture.clone(),
+                        sprite: Sprite {
+                            anchor: Anchor::TopLeft,
+                            ..default()
+                        },
+                        // centered on the six pixel high line
+                        transform: Transform::from_translation(Vec3::new(0.0, -1.0, 0.0)),
+                        visibility: Visibility::Hidden,
                         ..default()
                     },
-                    ..default()
-                },
-                ScoreUI(digit),
-            ));
-        }
-    }
-
-    {
-        let texture_handle = asset_server.load("score.png");
-        let texture_atlas =
-            TextureAtlas::from_grid(texture_handle, Vec2::new(6.0, 8.0), 8, 3, None, None);
-        let texture_atlas = texture_atlases.add(texture_atlas);
-
-        for digit in 0..TIMER_DIGITS + TIMER_SUB_DIGITS + 1 {
-            let texture_atlas = texture_atlas.clone();
-            commands.spawn((
-                SpriteSheetBundle {
-                    texture_atlas,
-                    transform: Transform::from_scale(Vec3::splat(0.5 * consts::SCALE))
-                        .with_translation(Vec3::new(
-                            timer_text_x_position(digit, TIMER_DIGITS, window.width()),
-                            top_text_y_position(window.height()),
-                            100.0,
-                        )),
-                    sprite: TextureAtlasSprite {
-                        index: 0,
-                        ..default()
-                    },
-                    ..default()
-                },
-                TimerUI(digit),
-            ));
-        }
+                    ServeIcon(variant.clone()),
+                    PaletteSwap::new(Part::Ball, texture),
+                ));
+                side.spawn((
+                    SpatialBundle::from_transform(Transform::from_translation(Vec3::new(
+                        text::ADVANCE as f32 + 1.0,
+                        0.0,
+                        0.0,
+                    ))),
+                    scoreboard_text("", 0, None, text::INK, text::FADED),
+                    ScoreboardText(variant),
+                ));
+            });
     }
 }
 
 pub fn update(
-    mut score_ui: Query<(&ScoreUI, &mut TextureAtlasSprite, &mut Transform)>,
-    mut timer_ui: Query<(&TimerUI, &mut TextureAtlasSprite), Without<ScoreUI>>,
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    mut score_ui: Query<(&mut BitmapText, Option<&Children>), With<ScoreUI>>,
+    mut timer_ui: Query<&mut BitmapText, (With<TimerUI>, Without<ScoreUI>)>,
+    mut glyphs: Query<(&Glyph, &mut Transform)>,
     state: Query<&State>,
-    window: Query<&Window>,
     time: Res<Time>,
 ) {
-    let window = window.single();
     let state = state.single();
-    let state_points = format!(
+    let theme = themes.current(&settings);
+    let (ink, faded) = (theme.ink, theme.readable(text::FADED));
+    let (mut score_ui, children) = score_ui.single_mut();
+    let score = format!(
         "{:0width$}",
         state.hits_with_velocity as usize,
         width = SCORE_DIGITS
     );
-
-    let state_timer = format!(
-        "{:0width$.1}",
-        state.game_time.elapsed_secs(),
-        width = TIMER_DIGITS + TIMER_SUB_DIGITS + 1
-    );
-
-    for (ui, mut sprite, mut transform) in &mut score_ui {
-        let position = state_points.len() - ui.0 - 1;
-        let value = &state_points[position..=position];
-        let value: usize = value.parse().expect("should be valid score");
-        if !matches!(state.game_state, GameState::Playing) {
-            transform.translation.y = top_text_y_position(window.height())
-                + (time.elapsed_seconds() * consts::SCALE * 0.5 + ui.0 as f32).sin()
-                    * consts::SCORE_ANIMATION_OFFSET;
+    score_ui.set_if_neq(score_text(&score, ink));
+    for child in children.into_iter().flatten() {
+        let Ok((glyph, mut transform)) = glyphs.get_mut(*child) else {
+            continue;
+        };
+        // counted from the right, glyphs of a longer old score go away next frame
+        let digit = score.len().saturating_sub(glyph.index + 1);
+        transform.translation.y = if !matches!(state.game_state, GameState::Playing) {
+            glyph.rest.y
+                + (time.elapsed_seconds() * consts::SCALE * 0.5 + digit as f32).sin()
+                    * consts::SCORE_ANIMATION_OFFSET
+                    / text::PIXEL
         } else {
-            transform.translation.y = top_text_y_position(window.height());
-        }
-        sprite.index = value;
+            glyph.rest.y
+        };
     }
 
-    const COMMA_SPRITE_INDEX: usize = 10;
+    timer_ui
+        .single_mut()
+        .set_if_neq(timer_text(state.game_time.elapsed_secs(), ink, faded));
+}
 
-    for (ui, mut sprite) in &mut timer_ui {
-        let position = state_timer.len() - ui.0 - 1;
-        let value = &state_timer[position..=position];
-        let value = value.parse().unwrap_or(COMMA_SPRITE_INDEX);
-        let value = if ui.0 < TIMER_SUB_DIGITS {
-            value + COMMA_SPRITE_INDEX + 1
+/// Each player's points and games, hidden on the title screen and for a missing player.
+#[allow(clippy::too_many_arguments)]
+pub fn update_scoreboard(
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    players: Res<PlayerSettings>,
+    screen: Option<Res<Screen>>,
+    state: Query<&State>,
+    mut sides: Query<(&ScoreboardUI, &mut Visibility)>,
+    mut icons: Query<(&ServeIcon, &mut Visibility), Without<ScoreboardUI>>,
+    mut texts: Query<(&ScoreboardText, &mut BitmapText)>,
+) {
+    let state = state.single();
+    for (side, mut visibility) in &mut sides {
+        let shown = screen.is_none() && settings.has_player(&side.0);
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
         } else {
-            value
-        };
-        sprite.index = value;
+            Visibility::Hidden
+        });
+    }
+    for (icon, mut visibility) in &mut icons {
+        visibility.set_if_neq(if icon.0 == state.server {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    let games = |variant: &Variant| (settings.format.best_of > 1).then(|| state.games.get(variant));
+    let theme = themes.current(&settings);
+    for (side, mut text) in &mut texts {
+        text.set_if_neq(scoreboard_text(
+            players.name(&settings, &side.0),
+            state.points.get(&side.0),
+            games(&side.0),
+            theme.readable(players.color(&side.0)),
+            theme.readable(text::FADED),
+        ));
     }
 }
 
 pub fn window_resized(
     resize_event: Res<Events<WindowResized>>,
     mut controls_ui: Query<&mut Transform, With<ControlsUI>>,
-    mut score_ui: Query<(&mut Transform, &ScoreUI), (Without<ControlsUI>, Without<TimerUI>)>,
-    mut timer_ui: Query<(&mut Transform, &TimerUI), (Without<ControlsUI>, Without<ScoreUI>)>,
+    mut score_ui: Query<&mut Transform, (With<ScoreUI>, Without<ControlsUI>, Without<TimerUI>)>,
+    mut timer_ui: Query<&mut Transform, (With<TimerUI>, Without<ControlsUI>, Without<ScoreUI>)>,
+    mut scoreboard_ui: Query<
+        (&mut Transform, &ScoreboardUI),
+        (Without<ControlsUI>, Without<ScoreUI>, Without<TimerUI>),
+    >,
 ) {
     let mut reader = resize_event.get_reader();
     for event in reader.iter(&resize_event) {
         let mut transform = controls_ui.single_mut();
         transform.translation.x = event.width * -0.5;
 
-        for (mut transform, ui) in &mut score_ui {
-            transform.translation.x = score_text_x_position(ui.0, SCORE_DIGITS);
-            transform.translation.y = top_text_y_position(event.height);
-        }
+        score_ui.single_mut().translation.y = top_text_y_position(event.height);
+
+        let mut transform = timer_ui.single_mut();
+        transform.translation.x = timer_text_x_position(event.width);
+        transform.translation.y = top_text_y_position(event.height);
 
-        for (mut transform, ui) in &mut timer_ui {
-            transform.translation.x = timer_text_x_position(ui.0, TIMER_DIGITS, event.width);
-            transform.translation.y = top_text_y_position(event.height);
+        for (mut transform, side) in &mut scoreboard_ui {
+            let position = scoreboard_position(&side.0, event.width, event.height);
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
         }
     }
 }
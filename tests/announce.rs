@@ -0,0 +1,129 @@
+mod common;
+
+use bevy::prelude::*;
+use bordtennis::{
+    announce::{situation, Announcements, Banner, BannerUI},
+    bat::Variant,
+    events::{BallHit, PointScored},
+    keymap,
+    settings::{GameMode, MatchFormat, Settings},
+    state::{GameState, Score, State},
+    stats::MatchStats,
+    text::BitmapText,
+};
+use common::Harness;
+
+fn format(points_per_game: u32, win_by: u32, best_of: u32) -> MatchFormat {
+    MatchFormat {
+        points_per_game,
+        win_by,
+        best_of,
+    }
+}
+
+fn state(light: u32, dark: u32) -> State {
+    State {
+        points: Score { light, dark },
+        ..State::default()
+    }
+}
+
+#[test]
+fn what_is_at_stake_after_a_point() {
+    let eleven = format(11, 2, 3);
+    assert_eq!(situation(&state(3, 2), &eleven), None);
+    assert_eq!(
+        situation(&state(10, 8), &eleven),
+        Some(Banner::new("GAME POINT", Some(Variant::Light)))
+    );
+    assert_eq!(
+        situation(&state(10, 10), &eleven),
+        Some(Banner::new("DEUCE", None))
+    );
+    assert_eq!(
+        situation(&state(11, 12), &eleven),
+        Some(Banner::new("GAME POINT", Some(Variant::Dark)))
+    );
+
+    let mut deciding = state(4, 10);
+    deciding.games = Score { light: 1, dark: 1 };
+    assert_eq!(
+        situation(&deciding, &eleven),
+        Some(Banner::new("MATCH POINT", Some(Variant::Dark)))
+    );
+
+    let mut game_won = state(0, 0);
+    game_won.game_scores.push(Score { light: 11, dark: 6 });
+    assert_eq!(
+        situation(&game_won, &eleven),
+        Some(Banner::new("GAME", Some(Variant::Light)))
+    );
+    game_won.match_over = true;
+    assert_eq!(situation(&game_won, &eleven), None);
+}
+
+#[test]
+fn the_same_banner_is_only_queued_once() {
+    let mut announcements = Announcements::default();
+    announcements.push(Banner::new("DEUCE", None));
+    announcements.push(Banner::new("DEUCE", None));
+    announcements.push(Banner::new("FASTEST SHOT", Some(Variant::Dark)));
+    assert_eq!(announcements.queue.len(), 2);
+}
+
+#[test]
+fn streaks_and_fastest_smashes_come_from_the_stats() {
+    let mut stats = MatchStats::default();
+    let point = |winner| PointScored { winner };
+    assert_eq!(stats.record_point(&point(Variant::Light)), 1);
+    assert_eq!(stats.record_point(&point(Variant::Light)), 2);
+    assert_eq!(stats.record_point(&point(Variant::Dark)), 1);
+    assert_eq!(stats.record_point(&point(Variant::Light)), 1);
+
+    let hit = |by, y: f32| BallHit {
+        by,
+        position: Vec2::ZERO,
+        velocity: Vec2::new(0.0, y),
+        offset: 0.0,
+    };
+    // the first shot of a match sets the record without beating one
+    assert!(!stats.record_hit(&hit(Variant::Light, 60.0), 64.0));
+    assert!(!stats.record_hit(&hit(Variant::Dark, -20.0), 64.0));
+    assert!(stats.record_hit(&hit(Variant::Dark, -62.0), 64.0));
+    assert!(!stats.record_hit(&hit(Variant::Light, 61.0), 64.0));
+}
+
+#[test]
+fn match_point_is_announced() {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Versus,
+        format: format(2, 1, 1),
+        ..Settings::default()
+    });
+    harness.tap(keymap::pause());
+    while harness.game_state() == &GameState::Playing {
+        harness.step();
+    }
+    harness.step();
+    let winner = match harness.game_state() {
+        GameState::Winner(winner) => winner.clone(),
+        other => panic!("expected a point to be won, got {other:?}"),
+    };
+    let showing = harness
+        .app
+        .world
+        .resource::<Announcements>()
+        .showing
+        .clone();
+    assert_eq!(
+        showing.map(|(banner, _)| banner),
+        Some(Banner::new("MATCH POINT", Some(winner)))
+    );
+    harness.step();
+    let world = &mut harness.app.world;
+    let (text, visibility) = world
+        .query_filtered::<(&BitmapText, &Visibility), With<BannerUI>>()
+        .single(world);
+    assert_eq!(visibility, Visibility::Inherited);
+    assert_eq!(text.sections[0].value, "MATCH POINT");
+}
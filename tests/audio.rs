@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bordtennis::{
+    audio::{intensity, stem_volumes, Effect, EffectSound, MusicMix},
+    ball::Ball,
+    bat::Variant,
+    events::BallHit,
+    settings::{GameMode, MatchFormat, Settings},
+    state::{Score, State},
+    tuning::GameTuning,
+};
+
+fn versus(points_per_game: u32) -> Settings {
+    Settings {
+        mode: GameMode::Versus,
+        format: MatchFormat {
+            points_per_game,
+            win_by: 2,
+            best_of: 3,
+        },
+        ..Settings::default()
+    }
+}
+
+#[test]
+fn stems_crossfade_with_intensity() {
+    assert_eq!(stem_volumes(0.0), [1.0, 0.0, 0.0]);
+    assert_eq!(stem_volumes(0.5), [0.5, 0.5, 0.0]);
+    assert_eq!(stem_volumes(1.0), [0.0, 1.0, 0.0]);
+    assert_eq!(stem_volumes(1.5), [0.0, 0.5, 0.5]);
+    assert_eq!(stem_volumes(2.0), [0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn hot_rallies_are_more_intense() {
+    let tuning = GameTuning::default();
+    let settings = versus(11);
+    let state = State::default();
+    let mut ball = Ball::served(&tuning, &Variant::Dark);
+    let calm = intensity(&state, &ball, &tuning, &settings);
+    ball.heat = 1.0;
+    ball.velocity = Vec2::new(0.0, ball.max_speed(&tuning));
+    assert!(intensity(&state, &ball, &tuning, &settings) > calm + 1.0);
+}
+
+#[test]
+fn match_point_and_close_scores_raise_intensity() {
+    let tuning = GameTuning::default();
+    let settings = versus(11);
+    let ball = Ball::served(&tuning, &Variant::Dark);
+    let mut state = State {
+        points: Score { light: 5, dark: 1 },
+        ..State::default()
+    };
+    let one_sided = intensity(&state, &ball, &tuning, &settings);
+    state.points = Score { light: 5, dark: 5 };
+    let close = intensity(&state, &ball, &tuning, &settings);
+    assert!(close > one_sided);
+
+    state.points = Score { light: 10, dark: 3 };
+    assert!(!state.match_point(&Variant::Light, &settings.format));
+    state.games = Score { light: 1, dark: 0 };
+    assert!(state.match_point(&Variant::Light, &settings.format));
+    assert!(!state.match_point(&Variant::Dark, &settings.format));
+    assert!(intensity(&state, &ball, &tuning, &settings) > one_sided);
+}
+
+#[test]
+fn hit_sounds_follow_the_ball_and_its_speed() {
+    let hit = |x: f32, speed: f32| BallHit {
+        by: Variant::Light,
+        position: Vec2::new(x, -20.0),
+        velocity: Vec2::new(0.0, speed),
+        offset: 0.0,
+    };
+    let left_soft = EffectSound::hit(&hit(-30.0, 10.0), 20.0, 64.0);
+    let center_hard = EffectSound::hit(&hit(0.0, 64.0), 20.0, 64.0);
+    let right = EffectSound::hit(&hit(10.0, 100.0), 20.0, 64.0);
+    assert_eq!(left_soft.pan, -1.0);
+    assert_eq!(center_hard.pan, 0.0);
+    assert_eq!(right.pan, 0.5);
+    assert!(left_soft.volume < center_hard.volume);
+    assert!(left_soft.speed < center_hard.speed);
+    assert_eq!(center_hard.volume, 1.0);
+    assert_eq!(
+        right,
+        EffectSound {
+            pan: 0.5,
+            ..center_hard
+        }
+    );
+}
+
+#[test]
+fn every_effect_sounds_different() {
+    let effects = [
+        Effect::Hit,
+        Effect::Bounce,
+        Effect::Serve,
+        Effect::Miss,
+        Effect::Pause,
+        Effect::Resume,
+        Effect::MenuMove,
+        Effect::MenuConfirm,
+        Effect::Countdown,
+    ];
+    let assets = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+    for (index, effect) in effects.iter().enumerate() {
+        let sample = effect.sample();
+        assert!(!sample.variants.is_empty());
+        for variant in sample.variants {
+            assert!(assets.join(variant).exists(), "{variant} is missing");
+            for other in &effects[index + 1..] {
+                assert!(
+                    !other.sample().variants.contains(variant),
+                    "{effect:?} and {other:?} both play {variant}"
+                );
+            }
+        }
+    }
+    assert!(Effect::Hit.sample().variants.len() > 1);
+}
+
+#[test]
+fn beats_count_from_when_the_stems_started() {
+    let mut mix = MusicMix::default();
+    assert_eq!(mix.beat(1.2), 2);
+    mix.started = Some(10.2);
+    assert_eq!(mix.beat(10.2), 0);
+    assert_eq!(mix.beat(10.69), 0);
+    assert_eq!(mix.beat(10.71), 1);
+    assert_eq!(mix.beat(12.21), 4);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use bordtennis::{
+    audio_settings::{AudioMenu, AudioSettings},
+    keymap,
+    settings::Settings,
+    state::GameState,
+    storage,
+};
+use common::Harness;
+
+#[test]
+fn volumes_step_within_range() {
+    let mut audio = AudioSettings::default();
+    audio.adjust(0, 1.0);
+    assert_eq!(audio.master, 1.0);
+    audio.adjust(1, -3.0);
+    assert!((audio.music - 0.7).abs() < 1e-6);
+    audio.adjust(2, -20.0);
+    assert_eq!(audio.effects, 0.0);
+
+    audio.master = 0.5;
+    assert!((audio.music_volume() - 0.35).abs() < 1e-6);
+    audio.muted = true;
+    assert_eq!(audio.music_volume(), 0.0);
+}
+
+#[test]
+fn settings_survive_a_save_and_load() {
+    let path = std::env::temp_dir()
+        .join(format!("bordtennis-audio-{}", std::process::id()))
+        .join("audio.ron");
+    assert_eq!(
+        storage::load_ron::<AudioSettings>(&path),
+        Ok(AudioSettings::default())
+    );
+    let audio = AudioSettings {
+        master: 0.8,
+        music: 0.2,
+        effects: 0.6,
+        muted: true,
+    };
+    storage::save_ron(&path, &audio).unwrap();
+    let loaded = storage::load_ron::<AudioSettings>(&path);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    assert_eq!(loaded, Ok(audio));
+}
+
+#[test]
+fn menu_changes_volume_and_takes_the_keys() {
+    let dir = std::env::temp_dir().join(format!("bordtennis-audio-menu-{}", std::process::id()));
+    let path = dir.join("audio.ron");
+    let mut harness = Harness::with_settings(Settings {
+        audio: Some(path.clone()),
+        ..Settings::default()
+    });
+    harness.tap(keymap::audio_menu());
+    harness.step();
+    assert!(harness.app.world.contains_resource::<AudioMenu>());
+
+    harness.tap(keymap::letter_down());
+    harness.tap(keymap::cursor_left());
+    harness.tap(keymap::pause());
+    assert_eq!(harness.game_state(), &GameState::NewGame);
+    harness.tap(keymap::menu_close());
+    harness.step();
+    assert!(!harness.app.world.contains_resource::<AudioMenu>());
+
+    harness.tap(keymap::mute());
+
+    let audio = harness.app.world.resource::<AudioSettings>().clone();
+    let saved = storage::load_ron::<AudioSettings>(&path);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!((audio.music - 0.9).abs() < 1e-6);
+    assert!(audio.muted);
+    assert_eq!(saved, Ok(audio));
+}
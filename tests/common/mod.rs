@@ -0,0 +1,84 @@
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use bordtennis::{
+    ball::Ball,
+    bat::{Bat, Variant},
+    headless,
+    replay::{Playback, Replay},
+    settings::Settings,
+    state::{GameState, State},
+};
+
+pub struct Harness {
+    pub app: App,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self::with_settings(Settings::default())
+    }
+
+    pub fn with_settings(settings: Settings) -> Self {
+        let mut app = headless::app(settings);
+        // runs the startup systems
+        app.update();
+        Self { app }
+    }
+
+    /// Plays back `replay` instead of reading the keyboard.
+    pub fn with_playback(replay: Replay) -> Self {
+        let mut app = headless::app(replay.settings());
+        app.insert_resource(Playback::new(replay));
+        app.update();
+        Self { app }
+    }
+
+    pub fn step(&mut self) {
+        self.app.update();
+        self.app.world.resource_mut::<Input<KeyCode>>().clear();
+    }
+
+    pub fn steps(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    pub fn press(&mut self, key: KeyCode) {
+        self.app.world.resource_mut::<Input<KeyCode>>().press(key);
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.app.world.resource_mut::<Input<KeyCode>>().release(key);
+    }
+
+    pub fn tap(&mut self, key: KeyCode) {
+        self.press(key);
+        self.step();
+        self.release(key);
+    }
+
+    pub fn state(&mut self) -> &State {
+        let world = &mut self.app.world;
+        world.query::<&State>().single(world)
+    }
+
+    pub fn game_state(&mut self) -> &GameState {
+        &self.state().game_state
+    }
+
+    pub fn ball(&mut self) -> &Ball {
+        let world = &mut self.app.world;
+        world.query::<&Ball>().single(world)
+    }
+
+    pub fn bat(&mut self, variant: Variant) -> &Bat {
+        let world = &mut self.app.world;
+        world
+            .query::<&Bat>()
+            .iter(world)
+            .find(|bat| bat.variant == variant)
+            .expect("bat should be spawned")
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bordtennis::{
+    ball::{self, Ball},
+    bat::Variant,
+    debug::execute,
+    settings::GameMode,
+    state::State,
+    tuning::GameTuning,
+};
+
+fn run(command: &str) -> (Result<String, String>, Ball, State, GameTuning) {
+    let mut ball = Ball::default();
+    let mut state = State::default();
+    let mut tuning = GameTuning::default();
+    let result = execute(command, &mut ball, &mut state, &mut tuning);
+    (result, ball, state, tuning)
+}
+
+#[test]
+fn sets_ball_velocity() {
+    let (result, ball, _, _) = run("velocity 3 -4");
+    assert!(result.is_ok());
+    assert_eq!(ball.velocity, Vec2::new(3.0, -4.0));
+}
+
+#[test]
+fn sets_ball_speed_keeping_direction() {
+    let (result, ball, _, _) = run("speed 30");
+    assert!(result.is_ok());
+    assert_eq!(ball.velocity, Vec2::new(0.0, -30.0));
+}
+
+#[test]
+fn sets_server() {
+    let (result, _, state, _) = run("server light");
+    assert!(result.is_ok());
+    assert_eq!(state.server, Variant::Light);
+    assert!(run("server nobody").0.is_err());
+}
+
+#[test]
+fn sets_tuning_field() {
+    let (result, _, _, tuning) = run("tune hit_radius 5.5");
+    assert!(result.is_ok());
+    assert_eq!(tuning.hit_radius, 5.5);
+    assert!(run("tune not_a_field 1").0.is_err());
+    assert!(run("tune hit_radius fast").0.is_err());
+}
+
+#[test]
+fn rejects_unknown_commands() {
+    assert!(run("fly").0.is_err());
+}
+
+#[test]
+fn predicted_trajectory_bounces_off_edges() {
+    let ball = Ball {
+        position: Vec2::new(9.0, 0.0),
+        velocity: Vec2::new(10.0, -10.0),
+        ..default()
+    };
+    let points = ball::trajectory(&ball, 10.0, ball::playable_range(GameMode::Versus));
+    let last = points.last().unwrap();
+    assert!(last.x < 10.0);
+    assert!(last.y <= Variant::Light.default_y_position());
+}
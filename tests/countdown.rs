@@ -0,0 +1,100 @@
+mod common;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bordtennis::{
+    countdown::{sprite_index, CountdownUI},
+    events::{CountdownTick, Served},
+    keymap,
+    settings::{GameMode, Settings},
+    state::GameState,
+};
+use common::Harness;
+
+fn counting_down(mode: GameMode) -> Harness {
+    let mut harness = Harness::with_settings(Settings {
+        mode,
+        countdown: true,
+        ..Settings::default()
+    });
+    harness.tap(keymap::pause());
+    harness
+}
+
+fn counting(harness: &mut Harness) -> bool {
+    matches!(harness.game_state(), GameState::Countdown { .. })
+}
+
+#[test]
+fn counts_down_before_the_serve() {
+    let mut harness = counting_down(GameMode::Versus);
+    assert_eq!(harness.game_state(), &GameState::Countdown { serve: true });
+    let position = harness.ball().position;
+    let mut ticks = ManualEventReader::<CountdownTick>::default();
+    let mut numbers = Vec::new();
+    let mut serves = 0;
+    let mut frames = 0;
+    while counting(&mut harness) {
+        harness.step();
+        frames += 1;
+        let world = &harness.app.world;
+        let events = world.resource::<Events<CountdownTick>>();
+        numbers.extend(ticks.iter(events).map(|tick| tick.number));
+        serves += world.resource::<Events<Served>>().len();
+    }
+    assert_eq!(numbers, vec![3, 2, 1]);
+    assert_eq!(serves, 1);
+    assert_eq!(frames, 180);
+    assert_eq!(harness.ball().position, position);
+    harness.steps(5);
+    assert_ne!(harness.ball().position, position);
+}
+
+#[test]
+fn shows_the_number() {
+    let mut harness = counting_down(GameMode::Versus);
+    harness.step();
+    let world = &mut harness.app.world;
+    let (sprite, visibility) = world
+        .query_filtered::<(&TextureAtlasSprite, &Visibility), With<CountdownUI>>()
+        .single(world);
+    assert_eq!(sprite.index, sprite_index(3));
+    assert_eq!(visibility, Visibility::Inherited);
+    assert_eq!(sprite_index(12), sprite_index(9));
+}
+
+#[test]
+fn unpausing_counts_down_without_serving_again() {
+    let mut harness = counting_down(GameMode::Versus);
+    while counting(&mut harness) {
+        harness.step();
+    }
+    harness.steps(10);
+    harness.tap(keymap::pause());
+    assert_eq!(harness.game_state(), &GameState::Paused);
+    harness.tap(keymap::pause());
+    assert_eq!(harness.game_state(), &GameState::Countdown { serve: false });
+    let position = harness.ball().position;
+    harness.steps(60);
+    assert!(counting(&mut harness));
+    assert_eq!(harness.ball().position, position);
+}
+
+#[test]
+fn only_practice_can_skip_it() {
+    let mut harness = counting_down(GameMode::Versus);
+    harness.tap(keymap::pause());
+    harness.step();
+    assert!(counting(&mut harness));
+
+    let mut harness = counting_down(GameMode::Practice);
+    harness.tap(keymap::pause());
+    harness.step();
+    assert_eq!(harness.game_state(), &GameState::Playing);
+}
+
+#[test]
+fn no_countdown_without_the_setting() {
+    let mut harness = Harness::new();
+    harness.tap(keymap::pause());
+    assert_eq!(harness.game_state(), &GameState::Playing);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use bevy::prelude::*;
+use bordtennis::{
+    bat::Variant,
+    events::{BallHit, MatchOver, Paused, PointScored},
+    keymap,
+    settings::{GameMode, MatchFormat, Settings},
+    state::GameState,
+};
+use common::Harness;
+
+#[derive(Debug, PartialEq)]
+enum Logged {
+    Hit(Variant),
+    Point(Variant),
+    MatchOver(Variant),
+    Paused,
+}
+
+#[derive(Resource, Default)]
+struct Log(Vec<Logged>);
+
+/// Listens the way a plugin outside the crate would.
+fn listen(
+    mut log: ResMut<Log>,
+    mut hits: EventReader<BallHit>,
+    mut points: EventReader<PointScored>,
+    mut match_over: EventReader<MatchOver>,
+    mut paused: EventReader<Paused>,
+) {
+    log.0.extend(paused.iter().map(|_| Logged::Paused));
+    log.0
+        .extend(hits.iter().map(|hit| Logged::Hit(hit.by.clone())));
+    log.0.extend(
+        points
+            .iter()
+            .map(|point| Logged::Point(point.winner.clone())),
+    );
+    log.0.extend(
+        match_over
+            .iter()
+            .map(|over| Logged::MatchOver(over.winner.clone())),
+    );
+}
+
+fn listening_harness() -> Harness {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Versus,
+        format: MatchFormat {
+            points_per_game: 1,
+            win_by: 1,
+            best_of: 1,
+        },
+        ..Settings::default()
+    });
+    harness
+        .app
+        .init_resource::<Log>()
+        .add_systems(Update, listen);
+    harness
+}
+
+fn play_point(harness: &mut Harness) {
+    while harness.game_state() == &GameState::Playing {
+        harness.step();
+    }
+}
+
+#[test]
+fn pausing_and_losing_the_match_are_sent() {
+    let mut harness = listening_harness();
+    harness.tap(keymap::pause());
+    harness.tap(keymap::pause());
+    harness.tap(keymap::pause());
+    play_point(&mut harness);
+    harness.step();
+
+    assert_eq!(
+        harness.app.world.resource::<Log>().0,
+        [
+            Logged::Paused,
+            Logged::Point(Variant::Dark),
+            Logged::MatchOver(Variant::Dark)
+        ]
+    );
+    assert_eq!(harness.state().hits_with_velocity, 0.0);
+}
+
+#[test]
+fn returned_serve_is_sent_and_scored() {
+    let mut harness = listening_harness();
+    harness.tap(keymap::pause());
+    while harness.ball().position.y > Variant::Light.default_y_position() + 2.0 {
+        harness.step();
+    }
+    harness.tap(keymap::swing(&Variant::Light));
+    play_point(&mut harness);
+    harness.step();
+
+    assert_eq!(
+        harness.app.world.resource::<Log>().0,
+        [
+            Logged::Hit(Variant::Light),
+            Logged::Point(Variant::Light),
+            Logged::MatchOver(Variant::Light)
+        ]
+    );
+    assert!(harness.state().hits_with_velocity > 0.0);
+}
@@ -0,0 +1,126 @@
+use bordtennis::{
+    bat::Variant,
+    export::{self, MatchReport, Players, CSV_FILE},
+    headless,
+    players::PlayerSettings,
+    settings::{Difficulty, GameMode, MatchFormat, Settings},
+    state::Score,
+    stats::PlayerStats,
+};
+
+fn report() -> MatchReport {
+    MatchReport {
+        timestamp: 1_700_000_000,
+        mode: GameMode::Cpu,
+        difficulty: Difficulty::Hard,
+        players: Players {
+            light: "light, the \"best\"".into(),
+            dark: "cpu (hard)".into(),
+        },
+        winner: Variant::Light,
+        games: Score { light: 2, dark: 1 },
+        game_scores: vec![
+            Score { light: 11, dark: 7 },
+            Score { light: 9, dark: 11 },
+            Score { light: 11, dark: 5 },
+        ],
+        duration: 321.5,
+        stats: Players {
+            light: PlayerStats {
+                hits: 4,
+                total_offset: 2.0,
+                ..PlayerStats::default()
+            },
+            dark: PlayerStats::default(),
+        },
+    }
+}
+
+#[test]
+fn csv_row_matches_the_header() {
+    let header = MatchReport::csv_header();
+    let row = report().csv_row();
+    assert!(row.starts_with("1700000000,cpu,hard,\"light, the \"\"best\"\"\",cpu (hard),light,2,1,11-7 9-11 11-5,321.50,4,"));
+    // the quoted name holds one of the commas
+    assert_eq!(header.matches(',').count() + 1, row.matches(',').count());
+    assert!(header.contains("light_average_offset"));
+    assert!(row.contains(",0.500,"));
+}
+
+#[test]
+fn json_holds_the_match() {
+    let json = report().to_json();
+    assert!(json.contains("\"winner\": \"Light\""));
+    assert!(json.contains("\"game_scores\""));
+    assert!(json.contains("\"total_offset\": 2.0"));
+}
+
+#[test]
+fn player_names_show_who_played() {
+    let settings = Settings {
+        mode: GameMode::Practice,
+        ..Settings::default()
+    };
+    let mut players = PlayerSettings::default();
+    assert_eq!(
+        export::player_name(&settings, &players, &Variant::Light),
+        "LIGHT"
+    );
+    assert_eq!(
+        export::player_name(&settings, &players, &Variant::Dark),
+        "wall"
+    );
+    players.light.name = "ANA".into();
+    players.dark.name = "BOB".into();
+    assert_eq!(
+        export::player_name(&settings, &players, &Variant::Light),
+        "ANA"
+    );
+    let settings = Settings {
+        mode: GameMode::Cpu,
+        difficulty: Difficulty::Easy,
+        ..Settings::default()
+    };
+    assert_eq!(
+        export::player_name(&settings, &players, &Variant::Light),
+        "ANA"
+    );
+    assert_eq!(
+        export::player_name(&settings, &players, &Variant::Dark),
+        "cpu (easy)"
+    );
+}
+
+#[test]
+fn finished_matches_are_exported() {
+    let dir = std::env::temp_dir().join(format!("bordtennis-export-{}", std::process::id()));
+    headless::run(
+        Settings {
+            mode: GameMode::Cpu,
+            format: MatchFormat {
+                points_per_game: 2,
+                win_by: 1,
+                best_of: 1,
+            },
+            seed: Some(11),
+            export_dir: Some(dir.clone()),
+            ..Settings::default()
+        },
+        2,
+        headless::ticks(1800),
+    );
+    let csv = std::fs::read_to_string(dir.join(CSV_FILE)).unwrap();
+    let json_files = std::fs::read_dir(&dir)
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            name.to_string_lossy().ends_with(".json")
+        })
+        .count();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], MatchReport::csv_header());
+    assert!(lines[1].contains(",cpu,normal,cpu (normal),cpu (normal),"));
+    assert_eq!(json_files, 2);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use bordtennis::{
+    ghost::{self, GapUI, Ghost},
+    keymap,
+    replay::Recorder,
+    settings::{Difficulty, GameMode, Settings},
+    state::GameState,
+    text::BitmapText,
+};
+use common::Harness;
+
+fn practice(replay_dir: Option<std::path::PathBuf>) -> Settings {
+    Settings {
+        mode: GameMode::Practice,
+        difficulty: Difficulty::Easy,
+        seed: Some(5),
+        autoplay: true,
+        replay_dir,
+        ..Settings::default()
+    }
+}
+
+fn finish_run(harness: &mut Harness) {
+    harness.tap(keymap::pause());
+    while !harness.state().match_over {
+        harness.step();
+    }
+}
+
+#[test]
+fn ghost_scores_the_same_as_the_recorded_run() {
+    let mut harness = Harness::with_settings(practice(None));
+    finish_run(&mut harness);
+    let replay = harness
+        .app
+        .world
+        .resource::<Recorder>()
+        .last
+        .clone()
+        .unwrap();
+    let score = harness.state().hits_with_velocity;
+    assert!(score > 0.0);
+    assert_eq!(ghost::score(&replay), score);
+    let best = harness.app.world.resource::<Ghost>().best.as_ref().unwrap();
+    assert_eq!(best.score, score);
+}
+
+#[test]
+fn ghost_plays_alongside_the_next_run() {
+    let mut harness = Harness::with_settings(practice(None));
+    finish_run(&mut harness);
+    while harness.game_state() != &GameState::Playing {
+        harness.tap(keymap::pause());
+    }
+    harness.steps(30);
+    let ghost = harness.app.world.resource::<Ghost>();
+    let run = ghost.run.as_ref().expect("ghost should be running");
+    assert!(run.ball.position.y < 20.0);
+    let world = &mut harness.app.world;
+    let gap = world
+        .query_filtered::<&BitmapText, bevy::prelude::With<GapUI>>()
+        .single(world);
+    assert!(gap.sections[0].value.starts_with(['+', '-']));
+}
+
+#[test]
+fn best_run_is_saved_and_loaded() {
+    let dir = std::env::temp_dir().join(format!("bordtennis-ghost-{}", std::process::id()));
+    let mut harness = Harness::with_settings(practice(Some(dir.clone())));
+    finish_run(&mut harness);
+    let score = harness.state().hits_with_velocity;
+    assert!(dir.join(ghost::BEST_RUN_FILE).exists());
+
+    let mut harness = Harness::with_settings(practice(Some(dir.clone())));
+    let best = harness
+        .app
+        .world
+        .resource::<Ghost>()
+        .best
+        .as_ref()
+        .map(|best| best.score);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(best, Some(score));
+    assert_eq!(harness.game_state(), &GameState::NewGame);
+}
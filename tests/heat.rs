@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use bordtennis::{
+    ball::Ball,
+    bat::{Bat, Direction, Variant},
+    settings::GameMode,
+    tuning::GameTuning,
+};
+
+/// Steps the ball into a swinging light bat, returning whether it was hit.
+fn hit(ball: &mut Ball, tuning: &GameTuning) -> bool {
+    let mut bat = Bat::new(Variant::Light, tuning);
+    bat.swinging = Direction::Down;
+    ball.position = Vec2::new(0.0, Variant::Light.default_y_position() + 1.0);
+    ball.last_hit = Variant::Dark;
+    let contacts = ball.step([&bat], tuning, GameMode::Versus, 100.0, 0.0);
+    contacts.hit.is_some()
+}
+
+#[test]
+fn every_hit_heats_the_rally_until_the_point_ends() {
+    let tuning = GameTuning::default();
+    let mut ball = Ball::served(&tuning, &Variant::Dark);
+    assert!(hit(&mut ball, &tuning));
+    assert_eq!(ball.heat, tuning.heat_per_hit);
+    for _ in 0..100 {
+        hit(&mut ball, &tuning);
+    }
+    assert_eq!(ball.heat, 1.0);
+    assert_eq!(ball.heat_level(), 2);
+
+    ball.serve(&tuning, &Variant::Dark);
+    assert_eq!(ball.heat, 0.0);
+    assert_eq!(ball.heat_level(), 0);
+}
+
+#[test]
+fn heat_lifts_the_speed_limit() {
+    let tuning = GameTuning::default();
+    let mut cold = Ball::served(&tuning, &Variant::Dark);
+    cold.velocity = Vec2::new(0.0, -1000.0);
+    hit(&mut cold, &tuning);
+    assert_eq!(cold.velocity.y, tuning.max_ball_speed);
+
+    let mut hot = Ball {
+        heat: 1.0,
+        ..Ball::served(&tuning, &Variant::Dark)
+    };
+    hot.velocity = Vec2::new(0.0, -1000.0);
+    hit(&mut hot, &tuning);
+    assert_eq!(
+        hot.velocity.y,
+        tuning.max_ball_speed * tuning.heat_max_speed_scale
+    );
+}
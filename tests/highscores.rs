@@ -0,0 +1,124 @@
+mod common;
+
+use bevy::{prelude::*, window::ReceivedCharacter};
+use bordtennis::{
+    audio_settings::AudioSettings,
+    bat::Variant,
+    highscores::{Entry, HighScoreUI, HighScores, InitialsEntry, TABLE_SIZE},
+    keymap,
+    settings::{Difficulty, GameMode, Settings},
+    state::GameState,
+    storage,
+    text::BitmapText,
+};
+use common::Harness;
+
+fn entry(initials: &str, score: u32) -> Entry {
+    Entry {
+        initials: initials.into(),
+        score,
+        time: 1.0,
+    }
+}
+
+#[test]
+fn table_keeps_the_best_ten() {
+    let mut scores = HighScores::default();
+    for score in 1..=12 {
+        scores.insert(
+            GameMode::Practice,
+            Difficulty::Normal,
+            entry("AAA", score * 10),
+        );
+    }
+    let table = scores.table(GameMode::Practice, Difficulty::Normal);
+    assert_eq!(table.len(), TABLE_SIZE);
+    assert_eq!(table[0].score, 120);
+    assert_eq!(table[TABLE_SIZE - 1].score, 30);
+    assert!(!scores.qualifies(GameMode::Practice, Difficulty::Normal, 30));
+    assert!(scores.qualifies(GameMode::Practice, Difficulty::Normal, 31));
+    assert_eq!(
+        scores.insert(GameMode::Practice, Difficulty::Normal, entry("BBB", 20)),
+        None
+    );
+    assert!(scores.table(GameMode::Cpu, Difficulty::Normal).is_empty());
+    assert!(scores.qualifies(GameMode::Practice, Difficulty::Hard, 1));
+    assert!(!scores.qualifies(GameMode::Practice, Difficulty::Hard, 0));
+}
+
+#[test]
+fn equal_scores_go_below_older_ones() {
+    let mut scores = HighScores::default();
+    scores.insert(GameMode::Versus, Difficulty::Normal, entry("OLD", 50));
+    let place = scores.insert(GameMode::Versus, Difficulty::Normal, entry("NEW", 50));
+    assert_eq!(place, Some(1));
+    assert_eq!(
+        scores.table(GameMode::Versus, Difficulty::Normal)[0].initials,
+        "OLD"
+    );
+}
+
+#[test]
+fn table_survives_a_save_and_load() {
+    let path = std::env::temp_dir()
+        .join(format!("bordtennis-scores-{}", std::process::id()))
+        .join("highscores.ron");
+    assert_eq!(
+        storage::load_ron::<HighScores>(&path),
+        Ok(HighScores::default())
+    );
+    let mut scores = HighScores::default();
+    scores.insert(GameMode::Cpu, Difficulty::Hard, entry("ABC", 123));
+    storage::save_ron(&path, &scores).unwrap();
+    let loaded = storage::load_ron::<HighScores>(&path);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    assert_eq!(loaded, Ok(scores));
+}
+
+#[test]
+fn initials_are_entered_after_a_high_score() {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Practice,
+        ..Settings::default()
+    });
+    harness.tap(keymap::pause());
+    while harness.ball().position.y > Variant::Light.default_y_position() + 2.0 {
+        harness.step();
+    }
+    harness.tap(keymap::swing(&Variant::Light));
+    while !harness.state().match_over {
+        harness.step();
+    }
+    // skips the instant replay
+    harness.tap(keymap::pause());
+    harness.step();
+    assert!(harness.app.world.contains_resource::<InitialsEntry>());
+
+    // letters that are also shortcuts only type
+    harness.press(keymap::mute());
+    for char in ['m', 'o', 'n'] {
+        harness.app.world.send_event(ReceivedCharacter {
+            window: Entity::PLACEHOLDER,
+            char,
+        });
+    }
+    harness.step();
+    harness.release(keymap::mute());
+    harness.tap(keymap::confirm());
+    assert!(!harness.app.world.contains_resource::<InitialsEntry>());
+    assert!(matches!(harness.game_state(), GameState::Winner(_)));
+    let score = harness.state().hits_with_velocity as u32;
+    let scores = harness.app.world.resource::<HighScores>();
+    let table = scores.table(GameMode::Practice, Difficulty::Normal);
+    assert_eq!(table.len(), 1);
+    assert_eq!(table[0].initials, "MON");
+    assert!(!harness.app.world.resource::<AudioSettings>().muted);
+    assert_eq!(table[0].score, score);
+
+    harness.tap(keymap::restart());
+    let world = &mut harness.app.world;
+    let text = world
+        .query_filtered::<&BitmapText, With<HighScoreUI>>()
+        .single(world);
+    assert!(text.sections[0].value.contains("MON"));
+}
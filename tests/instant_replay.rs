@@ -0,0 +1,58 @@
+#![cfg(not(feature = "singleplayer"))]
+
+mod common;
+
+use bevy::prelude::*;
+use bordtennis::{bat::Variant, instant_replay::InstantReplay, keymap, state::GameState};
+use common::Harness;
+
+fn lose_the_serve() -> Harness {
+    let mut harness = Harness::new();
+    harness.tap(keymap::pause());
+    while harness.game_state() == &GameState::Playing {
+        harness.step();
+    }
+    harness
+}
+
+fn camera_scale(harness: &mut Harness) -> f32 {
+    let world = &mut harness.app.world;
+    world
+        .query_filtered::<&OrthographicProjection, With<Camera2d>>()
+        .single(world)
+        .scale
+}
+
+fn replaying(harness: &Harness) -> bool {
+    harness.app.world.contains_resource::<InstantReplay>()
+}
+
+#[test]
+fn point_is_replayed_zoomed_in() {
+    let mut harness = lose_the_serve();
+    assert_eq!(harness.game_state(), &GameState::Winner(Variant::Dark));
+    harness.steps(10);
+    assert!(replaying(&harness));
+    assert!(camera_scale(&mut harness) < 1.0);
+}
+
+#[test]
+fn replay_ends_by_itself() {
+    let mut harness = lose_the_serve();
+    harness.steps(600);
+    assert!(!replaying(&harness));
+    assert_eq!(camera_scale(&mut harness), 1.0);
+    assert_eq!(harness.game_state(), &GameState::Winner(Variant::Dark));
+}
+
+#[test]
+fn pause_skips_the_replay_before_starting_the_next_point() {
+    let mut harness = lose_the_serve();
+    harness.steps(10);
+    harness.tap(keymap::pause());
+    assert!(!replaying(&harness));
+    assert_eq!(camera_scale(&mut harness), 1.0);
+    assert_eq!(harness.game_state(), &GameState::Winner(Variant::Dark));
+    harness.tap(keymap::pause());
+    assert_eq!(harness.game_state(), &GameState::Playing);
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use bordtennis::{
+    bat::Variant,
+    headless, keymap,
+    settings::{Difficulty, GameMode, MatchFormat, Settings},
+    state::{GameState, Score, State},
+};
+use common::Harness;
+
+#[test]
+fn game_needs_a_lead_to_be_won() {
+    let format = MatchFormat {
+        points_per_game: 3,
+        win_by: 2,
+        best_of: 3,
+    };
+    let mut state = State::default();
+    for winner in [Variant::Light, Variant::Light, Variant::Dark, Variant::Dark] {
+        state.score_point(winner, &format);
+    }
+    assert_eq!(state.points, Score { light: 2, dark: 2 });
+    state.score_point(Variant::Light, &format);
+    assert_eq!(state.games, Score::default());
+    state.score_point(Variant::Light, &format);
+    assert_eq!(state.games, Score { light: 1, dark: 0 });
+    assert_eq!(state.points, Score::default());
+    assert!(!state.match_over);
+}
+
+#[test]
+fn match_ends_after_winning_most_games() {
+    let format = MatchFormat {
+        points_per_game: 1,
+        win_by: 1,
+        best_of: 3,
+    };
+    let mut state = State::default();
+    state.score_point(Variant::Dark, &format);
+    state.score_point(Variant::Light, &format);
+    assert!(!state.match_over);
+    state.score_point(Variant::Dark, &format);
+    assert!(state.match_over);
+    assert_eq!(state.game_state, GameState::Winner(Variant::Dark));
+
+    state.start();
+    assert_eq!(state.games, Score::default());
+    assert!(!state.match_over);
+}
+
+#[test]
+fn serve_changes_every_two_points_and_every_game() {
+    let format = MatchFormat {
+        points_per_game: 3,
+        win_by: 2,
+        best_of: 3,
+    };
+    let mut state = State::default();
+    let mut servers = Vec::new();
+    for winner in [
+        Variant::Light,
+        Variant::Light,
+        Variant::Dark,
+        Variant::Dark,
+        Variant::Light,
+        Variant::Light,
+    ] {
+        state.score_point(winner, &format);
+        servers.push(state.server.clone());
+    }
+    // every point at 2-2, and the game is won at 4-2
+    assert_eq!(
+        servers,
+        [
+            Variant::Dark,
+            Variant::Light,
+            Variant::Light,
+            Variant::Dark,
+            Variant::Light,
+            Variant::Light,
+        ]
+    );
+    assert_eq!(state.games, Score { light: 1, dark: 0 });
+    state.score_point(Variant::Dark, &format);
+    state.score_point(Variant::Dark, &format);
+    assert_eq!(state.server, Variant::Dark);
+}
+
+#[test]
+fn dark_serves_first_in_every_match() {
+    let format = MatchFormat {
+        points_per_game: 1,
+        win_by: 1,
+        best_of: 3,
+    };
+    let mut state = State::default();
+    state.score_point(Variant::Dark, &format);
+    assert_eq!(state.server, Variant::Light);
+    state.score_point(Variant::Light, &format);
+    assert_eq!(state.server, Variant::Dark);
+    state.score_point(Variant::Light, &format);
+    assert!(state.match_over);
+    assert_eq!(state.server, Variant::Dark);
+
+    state.server = Variant::Light;
+    state.new_game();
+    assert_eq!(state.server, Variant::Dark);
+}
+
+#[test]
+fn practice_mode_has_no_dark_bat() {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Practice,
+        ..Settings::default()
+    });
+    let world = &mut harness.app.world;
+    let bats: Vec<_> = world
+        .query::<&bordtennis::bat::Bat>()
+        .iter(world)
+        .map(|bat| bat.variant.clone())
+        .collect();
+    assert_eq!(bats, vec![Variant::Light]);
+}
+
+#[test]
+fn cpu_returns_the_serve() {
+    let mut harness = Harness::with_settings(Settings {
+        difficulty: Difficulty::Hard,
+        seed: Some(1),
+        autoplay: true,
+        ..Settings::default()
+    });
+    harness.tap(keymap::pause());
+    for _ in 0..300 {
+        harness.step();
+        if harness.ball().last_hit == Variant::Light {
+            break;
+        }
+    }
+    assert_eq!(harness.ball().last_hit, Variant::Light);
+    assert_eq!(harness.game_state(), &GameState::Playing);
+}
+
+#[test]
+fn headless_plays_the_requested_matches() {
+    let settings = Settings {
+        seed: Some(7),
+        format: MatchFormat {
+            points_per_game: 2,
+            win_by: 1,
+            best_of: 1,
+        },
+        ..Settings::default()
+    };
+    let results = headless::run(settings, 2, headless::ticks(1800));
+    assert_eq!(results.len(), 2);
+    for result in &results {
+        let winner = result.winner.as_ref().unwrap();
+        assert_eq!(result.games.get(winner), 1);
+    }
+    assert!(headless::summary(&results).contains("match 2"));
+}
+
+#[test]
+fn headless_gives_up_on_endless_matches() {
+    let settings = Settings {
+        seed: Some(7),
+        ..Settings::default()
+    };
+    let results = headless::run(settings, 2, headless::ticks(1));
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result.winner.is_none()));
+    let summary = headless::summary(&results);
+    assert!(summary.contains("match 2: unfinished at 0-0"));
+    assert!(summary.contains("Light 0 - 0 Dark, 2 unfinished"));
+}
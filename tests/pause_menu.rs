@@ -0,0 +1,98 @@
+mod common;
+
+use bevy::prelude::*;
+use bordtennis::{
+    ball::Ball,
+    bat::Variant,
+    keymap,
+    pause_menu::{PauseAction, PauseMenu},
+    settings::{GameMode, MatchFormat, Settings},
+    state::{GameState, Score},
+};
+use common::Harness;
+
+fn paused_match() -> Harness {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Versus,
+        format: MatchFormat {
+            points_per_game: 5,
+            win_by: 1,
+            best_of: 1,
+        },
+        ..Settings::default()
+    });
+    // dark wins the first point, then the second one is paused half way
+    harness.tap(keymap::pause());
+    while harness.game_state() == &GameState::Playing {
+        harness.step();
+    }
+    // the first press may only skip the instant replay
+    while harness.game_state() != &GameState::Playing {
+        harness.tap(keymap::pause());
+    }
+    harness.steps(20);
+    harness.tap(keymap::pause());
+    harness.step();
+    assert!(harness.app.world.contains_resource::<PauseMenu>());
+    harness
+}
+
+fn choose(harness: &mut Harness, action: PauseAction) {
+    let index = PauseAction::ALL
+        .iter()
+        .position(|other| *other == action)
+        .unwrap();
+    for _ in 0..index {
+        harness.tap(keymap::menu_down());
+    }
+    harness.tap(keymap::confirm());
+    harness.step();
+}
+
+#[test]
+fn escape_resumes() {
+    let mut harness = paused_match();
+    let position = harness.ball().position;
+    harness.tap(KeyCode::Escape);
+    assert_eq!(harness.game_state(), &GameState::Playing);
+    harness.step();
+    assert!(!harness.app.world.contains_resource::<PauseMenu>());
+    assert_ne!(harness.ball().position, position);
+}
+
+#[test]
+fn restart_point_keeps_the_score() {
+    let mut harness = paused_match();
+    choose(&mut harness, PauseAction::RestartPoint);
+    assert_eq!(harness.game_state(), &GameState::Playing);
+    assert_eq!(harness.state().points, Score { light: 0, dark: 1 });
+    let served = Ball::served(&Default::default(), &Variant::Dark);
+    assert!(harness.ball().position.distance(served.position) < 1.0);
+}
+
+#[test]
+fn restart_match_starts_from_nothing() {
+    let mut harness = paused_match();
+    choose(&mut harness, PauseAction::RestartMatch);
+    assert_eq!(harness.game_state(), &GameState::Playing);
+    assert_eq!(harness.state().points, Score::default());
+    assert!(harness.state().game_time.elapsed_secs() < 0.1);
+}
+
+#[test]
+fn quit_goes_back_to_the_title() {
+    let mut harness = paused_match();
+    choose(&mut harness, PauseAction::Quit);
+    assert_eq!(harness.game_state(), &GameState::NewGame);
+    assert!(!harness.app.world.contains_resource::<PauseMenu>());
+}
+
+#[test]
+fn controls_are_shown_until_going_back() {
+    let mut harness = paused_match();
+    choose(&mut harness, PauseAction::Controls);
+    assert!(harness.app.world.resource::<PauseMenu>().show_controls);
+    harness.tap(KeyCode::Escape);
+    assert!(!harness.app.world.resource::<PauseMenu>().show_controls);
+    assert_eq!(harness.game_state(), &GameState::Paused);
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    window::ReceivedCharacter,
+};
+use bordtennis::{
+    audio_settings::AudioSettings,
+    bat::Variant,
+    keymap,
+    palette::{self, recolor, PaletteSwap, Part},
+    players::{PlayerLook, PlayerSettings, NAME_LENGTH},
+    settings::{GameMode, Settings},
+    storage, text,
+    theme::Theme,
+    title::{Screen, TitleAction},
+    ui::ScoreboardText,
+};
+use common::Harness;
+
+#[test]
+fn the_first_palette_is_the_one_the_sprites_use() {
+    let first = &palette::all()[0];
+    assert_eq!(first.colors[0], text::FADED);
+    assert_eq!(first.colors[2], text::INK);
+    assert_eq!(palette::background(), Color::hex("bdadf7").unwrap());
+    assert!(palette::all().len() > 1);
+    assert_eq!(palette::get(usize::MAX), first);
+}
+
+#[test]
+fn recoloring_swaps_the_palette_colors() {
+    let pixel = |color: Color| color.as_rgba_u8();
+    let base = &palette::all()[0];
+    let other = &palette::all()[1];
+    let data = [
+        pixel(base.colors[0]),
+        pixel(base.colors[2]),
+        [0, 0, 0, 0],
+        pixel(Color::WHITE),
+    ]
+    .concat();
+    let image = Image::new(
+        Extent3d {
+            width: 4,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    let recolored = recolor(&image, base, other);
+    assert_eq!(
+        recolored.data,
+        [
+            pixel(other.colors[0]),
+            pixel(other.colors[2]),
+            [0, 0, 0, 0],
+            pixel(Color::WHITE),
+        ]
+        .concat()
+    );
+}
+
+fn one_pixel(color: Color) -> Image {
+    Image::new(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        color.as_rgba_u8().to_vec(),
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+#[test]
+fn swapped_sprites_follow_the_palette_and_reloads() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_asset::<Image>()
+        .init_resource::<PlayerSettings>()
+        .add_systems(Update, palette::swap);
+    let base = &palette::all()[0];
+    let other = &palette::all()[1];
+    let source = app
+        .world
+        .resource_mut::<Assets<Image>>()
+        .add(one_pixel(base.colors[0]));
+    let sprite = app
+        .world
+        .spawn((PaletteSwap::new(Part::Ball, source.clone()), source.clone()))
+        .id();
+    let drawn = |app: &App| {
+        let texture = app.world.get::<Handle<Image>>(sprite).unwrap();
+        let image = app.world.resource::<Assets<Image>>().get(texture).unwrap();
+        image.data[..4].to_vec()
+    };
+
+    // an unknown palette is the first one, like on the players page
+    app.world.resource_mut::<PlayerSettings>().ball = usize::MAX;
+    app.update();
+    assert_eq!(app.world.get::<Handle<Image>>(sprite), Some(&source));
+
+    app.world.resource_mut::<PlayerSettings>().ball = 1;
+    app.update();
+    assert_eq!(drawn(&app), other.colors[0].as_rgba_u8());
+
+    // a reloaded sprite is recolored again
+    let mut images = app.world.resource_mut::<Assets<Image>>();
+    *images.get_mut(&source).unwrap() = one_pixel(base.colors[2]);
+    app.update();
+    app.update();
+    assert_eq!(drawn(&app), other.colors[2].as_rgba_u8());
+}
+
+#[test]
+fn names_are_short_capitals() {
+    let settings = Settings::default();
+    let mut players = PlayerSettings::default();
+    assert_eq!(players.name(&settings, &Variant::Light), "LIGHT");
+    for character in "ada lovelace".chars() {
+        players.type_character(&Variant::Light, character);
+    }
+    assert_eq!(players.light.name.len(), NAME_LENGTH);
+    assert_eq!(players.name(&settings, &Variant::Light), "ADALOV");
+    players.erase(&Variant::Light);
+    assert_eq!(players.name(&settings, &Variant::Light), "ADALO");
+
+    players.type_character(&Variant::Dark, 'x');
+    let cpu = Settings {
+        mode: GameMode::Cpu,
+        ..Settings::default()
+    };
+    assert_eq!(players.name(&cpu, &Variant::Dark), "CPU");
+}
+
+#[test]
+fn settings_are_saved_and_loaded() {
+    let path = std::env::temp_dir()
+        .join(format!("bordtennis-players-{}", std::process::id()))
+        .join("players.ron");
+    assert_eq!(
+        storage::load_ron::<PlayerSettings>(&path),
+        Ok(PlayerSettings::default())
+    );
+    let players = PlayerSettings {
+        light: PlayerLook {
+            name: "ADA".into(),
+            palette: 2,
+        },
+        dark: PlayerLook::default(),
+        ball: 1,
+    };
+    storage::save_ron(&path, &players).unwrap();
+    let loaded = storage::load_ron::<PlayerSettings>(&path);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    assert_eq!(loaded, Ok(players));
+}
+
+fn type_text(harness: &mut Harness, value: &str) {
+    for char in value.chars() {
+        harness.app.world.send_event(ReceivedCharacter {
+            window: Entity::PLACEHOLDER,
+            char,
+        });
+        harness.step();
+    }
+}
+
+#[test]
+fn picked_on_the_players_page() {
+    let dir = std::env::temp_dir().join(format!("bordtennis-players-page-{}", std::process::id()));
+    let path = dir.join("players.ron");
+    let mut harness = Harness::with_settings(Settings {
+        title_screen: true,
+        players: Some(path.clone()),
+        ..Settings::default()
+    });
+    harness.step();
+    let index = TitleAction::ALL
+        .iter()
+        .position(|action| *action == TitleAction::Players)
+        .unwrap();
+    for _ in 0..index {
+        harness.tap(keymap::menu_down());
+    }
+    harness.tap(keymap::confirm());
+    assert_eq!(
+        harness.app.world.resource::<Screen>(),
+        &Screen::Players { row: 0 }
+    );
+
+    // letters that are also shortcuts only type
+    harness.press(keymap::mute());
+    type_text(&mut harness, "m");
+    harness.release(keymap::mute());
+    type_text(&mut harness, "ax");
+    harness.tap(KeyCode::Back);
+    harness.tap(keymap::menu_down());
+    harness.tap(keymap::cursor_right());
+    harness.tap(keymap::menu_down());
+    harness.tap(keymap::menu_down());
+    harness.tap(keymap::cursor_right());
+    harness.step();
+
+    let players = harness.app.world.resource::<PlayerSettings>().clone();
+    assert_eq!(players.light.name, "MA");
+    assert_eq!(players.light.palette, 1);
+    assert_eq!(players.dark.palette, 1);
+    assert!(!harness.app.world.resource::<AudioSettings>().muted);
+    let world = &mut harness.app.world;
+    let mut name = |variant: Variant| {
+        world
+            .query::<(&ScoreboardText, &text::BitmapText)>()
+            .iter(world)
+            .find(|(side, _)| side.0 == variant)
+            .map(|(_, text)| text.sections[0].clone())
+            .unwrap()
+    };
+    let light = name(Variant::Light);
+    assert!(light.value.starts_with("MA "));
+    // in the ink when the bat's color is hard to read over the arena
+    let theme = Theme::default();
+    assert_eq!(light.color, theme.ink);
+    assert_eq!(theme.readable(palette::all()[1].colors[0]), theme.ink);
+    assert_eq!(name(Variant::Dark).color, palette::all()[1].colors[2]);
+
+    let saved = storage::load_ron::<PlayerSettings>(&path);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(saved, Ok(players));
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use bevy::prelude::*;
+use bordtennis::{
+    bat::{BatInput, Variant},
+    keymap,
+    pause_menu::PauseAction,
+    replay::{Playback, Recorder, Replay},
+    settings::{Difficulty, GameMode, MatchFormat, Settings},
+    state::GameState,
+    tuning::GameTuning,
+};
+use common::Harness;
+
+fn cpu_match() -> Settings {
+    Settings {
+        mode: GameMode::Cpu,
+        difficulty: Difficulty::Hard,
+        format: MatchFormat {
+            points_per_game: 3,
+            win_by: 1,
+            best_of: 1,
+        },
+        seed: Some(3),
+        autoplay: true,
+        ..Settings::default()
+    }
+}
+
+#[test]
+fn inputs_are_run_length_encoded() {
+    let mut replay = Replay::new(&Settings::default(), &GameTuning::default(), 0);
+    let idle = BatInput::default();
+    let swing = BatInput {
+        swing: true,
+        left: true,
+        ..default()
+    };
+    let run = BatInput {
+        run: true,
+        right: true,
+        ..default()
+    };
+    replay.push(&idle, &idle);
+    replay.push(&idle, &idle);
+    replay.push(&swing, &run);
+    replay.push(&idle, &idle);
+    assert_eq!(replay.inputs.len(), 3);
+    assert_eq!(replay.ticks(), 4);
+    assert_eq!(
+        replay.expand(),
+        vec![(idle, idle), (idle, idle), (swing, run), (idle, idle)]
+    );
+}
+
+#[test]
+fn edges_are_looked_up_by_tick() {
+    let mut replay = Replay::new(&Settings::default(), &GameTuning::default(), 0);
+    assert_eq!(replay.edge_at(0), None);
+    replay.set_edge(10.0);
+    replay.push(&BatInput::default(), &BatInput::default());
+    replay.set_edge(10.0);
+    replay.push(&BatInput::default(), &BatInput::default());
+    replay.set_edge(20.0);
+    replay.push(&BatInput::default(), &BatInput::default());
+    assert_eq!(replay.edges, vec![(0, 10.0), (2, 20.0)]);
+    assert_eq!(replay.edge_at(1), Some(10.0));
+    assert_eq!(replay.edge_at(2), Some(20.0));
+    assert_eq!(replay.edge_at(5), Some(20.0));
+}
+
+#[test]
+fn replay_survives_a_save_and_load() {
+    let mut replay = Replay::new(&cpu_match(), &GameTuning::default(), 42);
+    replay.set_edge(30.0);
+    replay.push(
+        &BatInput::default(),
+        &BatInput {
+            left: true,
+            ..default()
+        },
+    );
+    let path = std::env::temp_dir()
+        .join(format!("bordtennis-{}", std::process::id()))
+        .join("replay.ron");
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    assert_eq!(loaded, replay);
+    assert_eq!(loaded.settings().seed, Some(42));
+}
+
+#[test]
+fn loading_a_missing_replay_fails() {
+    assert!(Replay::load(std::path::Path::new("does/not/exist.ron")).is_err());
+}
+
+fn choose(harness: &mut Harness, action: PauseAction) {
+    harness.tap(keymap::pause());
+    harness.step();
+    let index = PauseAction::ALL
+        .iter()
+        .position(|other| *other == action)
+        .unwrap();
+    for _ in 0..index {
+        harness.tap(keymap::menu_down());
+    }
+    harness.tap(keymap::confirm());
+}
+
+/// Plays `game` to the end of the match and checks its replay ends the same way.
+fn plays_back_the_same(mut game: Harness) {
+    while game.app.world.resource::<Recorder>().last.is_none() {
+        if !matches!(game.game_state(), GameState::Playing) && !game.state().match_over {
+            game.tap(keymap::pause());
+        }
+        game.step();
+    }
+    let replay = game.app.world.resource::<Recorder>().last.clone().unwrap();
+    let game_state = format!("{:?}", game.game_state());
+    let (points, games) = (game.state().points, game.state().games);
+    let ball = game.ball().position;
+    let light = game.bat(Variant::Light).position_x;
+
+    let mut playback = Harness::with_playback(replay);
+    while !playback.app.world.resource::<Playback>().finished() {
+        playback.step();
+    }
+    playback.steps(10);
+    assert_eq!(format!("{:?}", playback.game_state()), game_state);
+    assert_eq!(playback.state().points, points);
+    assert_eq!(playback.state().games, games);
+    assert_eq!(playback.ball().position, ball);
+    assert_eq!(playback.bat(Variant::Light).position_x, light);
+}
+
+#[test]
+fn playback_ends_like_the_recorded_match() {
+    plays_back_the_same(Harness::with_settings(cpu_match()));
+}
+
+#[test]
+fn restarts_from_the_pause_menu_are_played_back() {
+    let mut game = Harness::with_settings(cpu_match());
+    game.tap(keymap::pause());
+    game.steps(40);
+    choose(&mut game, PauseAction::RestartPoint);
+    assert_eq!(game.game_state(), &GameState::Playing);
+    let replay = game
+        .app
+        .world
+        .resource::<Recorder>()
+        .replay
+        .clone()
+        .unwrap();
+    assert!(replay.reset_at(replay.ticks()));
+    plays_back_the_same(game);
+
+    let mut game = Harness::with_settings(cpu_match());
+    game.tap(keymap::pause());
+    game.steps(40);
+    choose(&mut game, PauseAction::RestartMatch);
+    game.step();
+    let replay = game
+        .app
+        .world
+        .resource::<Recorder>()
+        .replay
+        .clone()
+        .unwrap();
+    assert!(replay.ticks() <= 2);
+    plays_back_the_same(game);
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use bevy::{prelude::*, window::WindowResized};
+use bordtennis::{
+    bat::Variant,
+    keymap,
+    settings::{GameMode, MatchFormat, Settings},
+    state::GameState,
+    text::{self, BitmapText},
+    ui::{ScoreboardText, ScoreboardUI, ServeIcon},
+};
+use common::Harness;
+
+fn side_text(harness: &mut Harness, variant: Variant) -> String {
+    let world = &mut harness.app.world;
+    world
+        .query::<(&ScoreboardText, &BitmapText)>()
+        .iter(world)
+        .find(|(side, _)| side.0 == variant)
+        .map(|(_, text)| text.sections.iter().map(|s| s.value.as_str()).collect())
+        .expect("both sides should be spawned")
+}
+
+fn side_visible(harness: &mut Harness, variant: Variant) -> bool {
+    let world = &mut harness.app.world;
+    world
+        .query::<(&ScoreboardUI, &Visibility)>()
+        .iter(world)
+        .any(|(side, visibility)| side.0 == variant && visibility != Visibility::Hidden)
+}
+
+fn icon_visible(harness: &mut Harness, variant: Variant) -> bool {
+    let world = &mut harness.app.world;
+    world
+        .query::<(&ServeIcon, &Visibility)>()
+        .iter(world)
+        .any(|(icon, visibility)| icon.0 == variant && visibility != Visibility::Hidden)
+}
+
+#[test]
+fn each_side_shows_its_points_and_games() {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Versus,
+        format: MatchFormat {
+            points_per_game: 5,
+            win_by: 1,
+            best_of: 3,
+        },
+        ..Settings::default()
+    });
+    harness.tap(keymap::pause());
+    while harness.game_state() == &GameState::Playing {
+        harness.step();
+    }
+    harness.step();
+    let winner = match harness.game_state() {
+        GameState::Winner(winner) => winner.clone(),
+        other => panic!("expected a point to be won, got {other:?}"),
+    };
+    let name = match winner {
+        Variant::Light => "LIGHT",
+        Variant::Dark => "DARK",
+    };
+    assert_eq!(
+        side_text(&mut harness, winner.clone()),
+        format!("{name:<6}  1  games 0")
+    );
+    assert!(side_text(&mut harness, winner.opponent()).contains("  0  games 0"));
+}
+
+#[test]
+fn serve_icon_marks_the_server() {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Versus,
+        format: MatchFormat {
+            points_per_game: 11,
+            win_by: 2,
+            best_of: 1,
+        },
+        ..Settings::default()
+    });
+    harness.step();
+    assert!(icon_visible(&mut harness, Variant::Dark));
+    assert!(!icon_visible(&mut harness, Variant::Light));
+
+    // nobody hits the ball, so the points come quickly
+    for _ in 0..2 {
+        while harness.game_state() != &GameState::Playing {
+            harness.tap(keymap::pause());
+        }
+        while harness.game_state() == &GameState::Playing {
+            harness.step();
+        }
+    }
+    harness.step();
+    assert_eq!(harness.state().server, Variant::Light);
+    assert!(icon_visible(&mut harness, Variant::Light));
+    assert!(!icon_visible(&mut harness, Variant::Dark));
+    assert_eq!(
+        harness.ball().position.y,
+        Variant::Light.default_y_position()
+    );
+}
+
+#[test]
+fn cpu_and_practice_sides() {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Cpu,
+        ..Settings::default()
+    });
+    harness.step();
+    assert_eq!(side_text(&mut harness, Variant::Dark), "CPU     0");
+    assert!(side_visible(&mut harness, Variant::Dark));
+
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Practice,
+        ..Settings::default()
+    });
+    harness.step();
+    assert!(side_visible(&mut harness, Variant::Light));
+    assert!(!side_visible(&mut harness, Variant::Dark));
+}
+
+#[test]
+fn stays_in_the_corners_when_resized() {
+    let mut harness = Harness::new();
+    harness.step();
+    let window = harness
+        .app
+        .world
+        .query_filtered::<Entity, With<Window>>()
+        .single(&harness.app.world);
+    harness.app.world.send_event(WindowResized {
+        window,
+        width: 400.0,
+        height: 300.0,
+    });
+    harness.step();
+    let world = &mut harness.app.world;
+    for (side, transform) in world.query::<(&ScoreboardUI, &Transform)>().iter(world) {
+        assert_eq!(transform.translation.x, -200.0 + text::PIXEL);
+        match side.0 {
+            Variant::Light => assert!(transform.translation.y < 0.0),
+            Variant::Dark => assert_eq!(transform.translation.y, 150.0 - text::PIXEL),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+#![cfg(not(feature = "singleplayer"))]
+
+mod common;
+
+use bordtennis::{
+    ball::Ball,
+    bat::{Direction, Variant},
+    keymap,
+    state::GameState,
+};
+use common::Harness;
+
+#[test]
+fn starts_on_new_game_screen() {
+    let mut harness = Harness::new();
+    harness.steps(10);
+    assert_eq!(harness.game_state(), &GameState::NewGame);
+    assert_eq!(harness.ball().position, Ball::default().position);
+}
+
+#[test]
+fn dark_serve_reaches_light() {
+    let mut harness = Harness::new();
+    harness.tap(keymap::pause());
+    let light_y = Variant::Light.default_y_position();
+    let mut lowest = f32::MAX;
+    for _ in 0..300 {
+        harness.step();
+        lowest = lowest.min(harness.ball().position.y);
+        if lowest <= light_y + 0.5 {
+            break;
+        }
+    }
+    assert!(lowest <= light_y + 0.5, "ball only reached y = {lowest}");
+}
+
+#[test]
+fn missed_ball_gives_point_to_dark() {
+    let mut harness = Harness::new();
+    harness.tap(keymap::pause());
+    harness.steps(300);
+    assert_eq!(harness.game_state(), &GameState::Winner(Variant::Dark));
+    assert_eq!(harness.ball().position, Ball::default().position);
+}
+
+#[test]
+fn light_returns_served_ball() {
+    let mut harness = Harness::new();
+    harness.tap(keymap::pause());
+    while harness.ball().position.y > Variant::Light.default_y_position() + 2.0 {
+        harness.step();
+    }
+    harness.tap(keymap::swing(&Variant::Light));
+    harness.steps(5);
+    let ball = harness.ball();
+    assert_eq!(ball.last_hit, Variant::Light);
+    assert!(ball.velocity.y > 0.0);
+    assert_eq!(harness.game_state(), &GameState::Playing);
+}
+
+#[test]
+fn pause_stops_the_ball() {
+    let mut harness = Harness::new();
+    harness.tap(keymap::pause());
+    harness.steps(10);
+    harness.tap(keymap::pause());
+    assert_eq!(harness.game_state(), &GameState::Paused);
+    let position = harness.ball().position;
+    harness.steps(30);
+    assert_eq!(harness.ball().position, position);
+}
+
+#[test]
+fn restart_resets_ball_and_bats() {
+    let mut harness = Harness::new();
+    harness.tap(keymap::pause());
+    harness.press(keymap::right(&Variant::Light));
+    harness.press(keymap::left(&Variant::Dark));
+    harness.steps(30);
+    harness.release(keymap::right(&Variant::Light));
+    harness.release(keymap::left(&Variant::Dark));
+    assert!(harness.bat(Variant::Light).position_x > 0.0);
+    assert!(harness.bat(Variant::Dark).position_x < 0.0);
+    assert_ne!(harness.ball().position, Ball::default().position);
+
+    harness.tap(keymap::restart());
+    assert_eq!(harness.game_state(), &GameState::NewGame);
+    assert_eq!(harness.ball().position, Ball::default().position);
+    assert_eq!(harness.ball().velocity, Ball::default().velocity);
+    for variant in [Variant::Light, Variant::Dark] {
+        let bat = harness.bat(variant);
+        assert_eq!(bat.position_x, 0.0);
+        assert_eq!(bat.swinging, Direction::None);
+    }
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use bevy::prelude::*;
+use bordtennis::{
+    bat::Variant,
+    events::{BallHit, PointScored},
+    keymap,
+    players::PlayerSettings,
+    settings::{GameMode, Settings},
+    state::GameState,
+    stats::{self, MatchStats, StatsUI},
+    text::BitmapText,
+};
+use common::Harness;
+
+#[test]
+fn hits_and_points_are_counted_per_player() {
+    let mut stats = MatchStats::default();
+    let hit = |by: Variant, y: f32, offset: f32| BallHit {
+        by,
+        position: Vec2::ZERO,
+        velocity: Vec2::new(0.0, y),
+        offset,
+    };
+    stats.record_hit(&hit(Variant::Light, 30.0, -1.0), 64.0);
+    stats.record_hit(&hit(Variant::Dark, -60.0, 2.0), 64.0);
+    stats.record_hit(&hit(Variant::Light, 20.0, 0.5), 64.0);
+    stats.record_point(&PointScored {
+        winner: Variant::Light,
+    });
+    stats.record_hit(&hit(Variant::Light, 10.0, 0.0), 64.0);
+
+    assert_eq!(stats.light.hits, 3);
+    assert_eq!(stats.light.longest_rally, 2);
+    assert_eq!(stats.light.fastest_shot, 30.0);
+    assert_eq!(stats.light.average_offset(), 0.5);
+    assert_eq!(stats.light.smashes, 0);
+    assert_eq!(stats.light.misses, 0);
+    assert_eq!(stats.dark.hits, 1);
+    assert_eq!(stats.dark.smashes, 1);
+    assert_eq!(stats.dark.misses, 1);
+}
+
+#[test]
+fn bat_distance_only_counts_while_moving() {
+    let mut stats = MatchStats::default();
+    stats.record_bat(&Variant::Light, 0.0, true);
+    stats.record_bat(&Variant::Light, 2.0, true);
+    stats.record_bat(&Variant::Light, -1.0, true);
+    // a reset between points isn't movement
+    stats.record_bat(&Variant::Light, 0.0, false);
+    stats.record_bat(&Variant::Light, 0.5, true);
+    assert_eq!(stats.light.distance, 5.5);
+    assert_eq!(stats.dark.distance, 0.0);
+}
+
+#[test]
+fn returned_serve_shows_up_in_the_stats() {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Versus,
+        ..Settings::default()
+    });
+    harness.tap(keymap::pause());
+    harness.press(keymap::right(&Variant::Light));
+    harness.steps(5);
+    harness.release(keymap::right(&Variant::Light));
+    while harness.ball().position.y > Variant::Light.default_y_position() + 2.0 {
+        harness.step();
+    }
+    harness.tap(keymap::swing(&Variant::Light));
+    while harness.game_state() == &GameState::Playing {
+        harness.step();
+    }
+    let stats = harness.app.world.resource::<MatchStats>();
+    assert_eq!(stats.light.hits, 1);
+    assert!(stats.light.fastest_shot > 0.0);
+    assert!(stats.light.distance > 0.0);
+    assert_eq!(stats.light.misses, 0);
+    assert_eq!(stats.dark.hits, 0);
+    assert_eq!(stats.dark.misses, 1);
+    let columns = stats::columns(stats, &[(Variant::Light, "ANA"), (Variant::Dark, "DARK")]);
+    assert!(columns[0].contains("longest rally"));
+    assert!(columns[1].starts_with("ANA\n1\n1\n"));
+    assert!(columns[2].starts_with("DARK\n0\n"));
+}
+
+#[test]
+fn results_screen_shows_the_entered_names() {
+    let mut harness = Harness::with_settings(Settings {
+        mode: GameMode::Versus,
+        ..Settings::default()
+    });
+    harness
+        .app
+        .world
+        .resource_mut::<PlayerSettings>()
+        .light
+        .name = "ANA".into();
+    harness.tap(keymap::pause());
+    harness.steps(600);
+    assert_eq!(harness.game_state(), &GameState::Winner(Variant::Dark));
+    let world = &mut harness.app.world;
+    let columns: Vec<String> = world
+        .query_filtered::<&BitmapText, With<StatsUI>>()
+        .iter(world)
+        .map(|text| text.sections[0].value.clone())
+        .collect();
+    assert!(columns.iter().any(|column| column.starts_with("ANA\n")));
+    assert!(columns.iter().any(|column| column.starts_with("DARK\n")));
+    assert!(!columns.iter().any(|column| column.starts_with("LIGHT")));
+}
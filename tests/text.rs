@@ -0,0 +1,103 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::{prelude::*, sprite::Anchor};
+use bordtennis::{
+    state::State,
+    text::{self, glyph_index, wrap, BitmapText, Glyph, ADVANCE, LINE_HEIGHT},
+    ui::{ScoreUI, TimerUI},
+};
+use common::Harness;
+
+#[test]
+fn words_wrap_to_the_line_length() {
+    assert_eq!(
+        wrap("game point light", Some(10)),
+        vec!["game point", "light"]
+    );
+    assert_eq!(wrap("match\npoint", None), vec!["match", "point"]);
+    assert_eq!(wrap("deuce", Some(3)), vec!["deu", "ce"]);
+    assert_eq!(wrap("", Some(3)), vec![""]);
+}
+
+#[test]
+fn lower_case_uses_the_capitals() {
+    assert_eq!(glyph_index('a'), glyph_index('A'));
+    assert_eq!(glyph_index('0'), 16);
+    assert_eq!(glyph_index('é'), glyph_index('?'));
+}
+
+#[test]
+fn lines_are_aligned_within_the_block() {
+    let text = BitmapText::new("ab\nc", text::INK)
+        .with_alignment(TextAlignment::Right)
+        .with_anchor(Anchor::TopLeft);
+    assert_eq!(
+        text.size(),
+        UVec2::new(2 * ADVANCE - 1, 2 * LINE_HEIGHT - 1)
+    );
+    let positions: Vec<Vec2> = text.layout().iter().map(|glyph| glyph.position).collect();
+    assert_eq!(
+        positions,
+        vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(ADVANCE as f32, 0.0),
+            Vec2::new(ADVANCE as f32, -(LINE_HEIGHT as f32)),
+        ]
+    );
+}
+
+#[test]
+fn anchor_moves_the_block() {
+    let text = BitmapText::new("abc", text::INK).with_anchor(Anchor::BottomRight);
+    let size = text.size().as_vec2();
+    assert_eq!(text.layout()[0].position, Vec2::new(-size.x, size.y));
+}
+
+#[test]
+fn spaces_have_no_glyph() {
+    let text = BitmapText::new("a b", text::INK);
+    let indices: Vec<usize> = text.layout().iter().map(|glyph| glyph.index).collect();
+    assert_eq!(indices, vec![0, 2]);
+}
+
+#[test]
+fn timer_keeps_counting_past_999_seconds() {
+    let mut harness = Harness::new();
+    let world = &mut harness.app.world;
+    world
+        .query::<&mut State>()
+        .single_mut(world)
+        .game_time
+        .tick(Duration::from_secs_f32(1234.5));
+    harness.steps(2);
+
+    let world = &mut harness.app.world;
+    let (entity, timer) = world
+        .query_filtered::<(Entity, &BitmapText), With<TimerUI>>()
+        .single(world);
+    let value: String = timer
+        .sections
+        .iter()
+        .map(|section| section.value.as_str())
+        .collect();
+    assert_eq!(value, "1234.5");
+    let glyphs = world
+        .query::<(&Glyph, &Parent)>()
+        .iter(world)
+        .filter(|(_, parent)| parent.get() == entity)
+        .count();
+    assert_eq!(glyphs, value.len());
+}
+
+#[test]
+fn score_is_drawn_with_the_font() {
+    let mut harness = Harness::new();
+    harness.steps(2);
+    let world = &mut harness.app.world;
+    let score = world
+        .query_filtered::<&BitmapText, With<ScoreUI>>()
+        .single(world);
+    assert_eq!(score.sections[0].value, "0000000");
+}
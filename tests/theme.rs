@@ -0,0 +1,241 @@
+mod common;
+
+use bevy::prelude::*;
+use bordtennis::{
+    audio::{Music, MusicMix},
+    bat::Variant,
+    keymap, palette,
+    players::{PlayerRow, PlayerSettings},
+    settings::Settings,
+    table::Net,
+    text::{self, BitmapText},
+    theme::{Particle, Theme, ThemeManifest, Themes, ThemesHandle, THEMES_PATH},
+    title::{Screen, SetupRow, TitleAction, TitleBody, TitleHeading, TitleItem},
+    ui::ScoreUI,
+};
+use common::Harness;
+
+fn manifest() -> ThemeManifest {
+    let path = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), THEMES_PATH);
+    let source = std::fs::read_to_string(path).expect("themes manifest should exist");
+    ron::from_str(&source).expect("themes manifest should parse")
+}
+
+fn loaded(settings: Settings) -> Harness {
+    let mut harness = Harness::with_settings(settings);
+    for _ in 0..600 {
+        let world = &harness.app.world;
+        let handle = &world.resource::<ThemesHandle>().0;
+        if world.resource::<Assets<ThemeManifest>>().contains(handle) {
+            break;
+        }
+        harness.step();
+    }
+    harness.steps(2);
+    harness
+}
+
+/// The match stems playing, the menu track left out.
+fn stems(harness: &mut Harness) -> Vec<String> {
+    let world = &mut harness.app.world;
+    let handles: Vec<Handle<AudioSource>> = world
+        .query::<(&Music, &Handle<AudioSource>)>()
+        .iter(world)
+        .filter(|(music, _)| !matches!(music, Music::Menu))
+        .map(|(_, handle)| handle.clone())
+        .collect();
+    let asset_server = world.resource::<AssetServer>();
+    let mut stems: Vec<String> = handles
+        .into_iter()
+        .map(|handle| {
+            let path = asset_server.get_handle_path(handle).unwrap();
+            path.path().display().to_string()
+        })
+        .collect();
+    stems.sort();
+    stems
+}
+
+fn particles(harness: &mut Harness) -> usize {
+    let world = &mut harness.app.world;
+    world.query::<&Particle>().iter(world).count()
+}
+
+#[test]
+fn manifest_has_the_arenas() {
+    let manifest = manifest();
+    assert_eq!(manifest.themes[0], Theme::default());
+    let names: Vec<_> = manifest
+        .themes
+        .iter()
+        .map(|theme| &theme.name[..])
+        .collect();
+    for name in ["classic green", "blue tournament", "night gym", "beach"] {
+        assert!(names.contains(&name), "no {name} theme");
+    }
+    let assets = format!("{}/assets", env!("CARGO_MANIFEST_DIR"));
+    for theme in &manifest.themes {
+        assert!(std::path::Path::new(&assets).join(&theme.table).exists());
+        if let Some(net) = &theme.net {
+            assert!(std::path::Path::new(&assets).join(&net.sprite).exists());
+        }
+        for stem in theme.music.iter().flat_map(|music| &music.stems) {
+            assert!(std::path::Path::new(&assets).join(stem).exists());
+        }
+    }
+    assert!(manifest.themes.iter().any(|theme| theme.music.is_some()));
+}
+
+#[test]
+fn text_stands_out_from_every_background() {
+    let lightness = |color: Color| color.as_hsla_f32()[2];
+    for theme in manifest().themes {
+        let contrast = |color: Color| (lightness(color) - lightness(theme.background)).abs();
+        assert!(
+            contrast(theme.ink) > 0.4,
+            "{} text is hard to read",
+            theme.name
+        );
+        // names are drawn in the colors of the bats, stepped through every palette
+        let mut players = PlayerSettings::default();
+        for _ in palette::all() {
+            for variant in [Variant::Light, Variant::Dark] {
+                let color = theme.readable(players.color(&variant));
+                assert!(
+                    contrast(color) >= 0.4,
+                    "{} names are hard to read",
+                    theme.name
+                );
+                players.adjust(&PlayerRow::Bat(variant), 1);
+            }
+        }
+        assert!(contrast(theme.readable(text::FADED)) >= 0.4);
+    }
+}
+
+#[test]
+fn unknown_themes_are_the_first_one() {
+    let themes = Themes(manifest().themes);
+    assert_eq!(themes.get(usize::MAX), &Theme::default());
+    assert_eq!(themes.get(1), &manifest().themes[1]);
+}
+
+#[test]
+fn particles_and_net_follow_the_theme() {
+    let themes = manifest().themes;
+    let index = themes
+        .iter()
+        .position(|theme| theme.particles.is_some() && theme.net.is_some())
+        .unwrap();
+    let mut harness = loaded(Settings::default());
+    assert_eq!(particles(&mut harness), 0);
+
+    harness.app.world.resource_mut::<Settings>().theme = index;
+    harness.steps(2);
+    let count = themes[index].particles.as_ref().unwrap().count;
+    assert_eq!(particles(&mut harness), count);
+    let world = &mut harness.app.world;
+    let net = world
+        .query_filtered::<&Visibility, With<Net>>()
+        .single(world);
+    assert_eq!(net, Visibility::Inherited);
+}
+
+#[test]
+fn picked_on_the_setup_screen() {
+    let mut harness = loaded(Settings {
+        title_screen: true,
+        ..Settings::default()
+    });
+    let index = TitleAction::ALL
+        .iter()
+        .position(|action| *action == TitleAction::Practice)
+        .unwrap();
+    for _ in 0..index {
+        harness.tap(keymap::menu_down());
+    }
+    harness.tap(keymap::confirm());
+    let Screen::Setup(setup) = harness.app.world.resource::<Screen>() else {
+        panic!("not on the setup screen");
+    };
+    assert_eq!(setup.themes.len(), manifest().themes.len());
+    let theme = setup.rows().iter().position(|row| *row == SetupRow::Theme);
+
+    for _ in 0..theme.unwrap() {
+        harness.tap(keymap::menu_down());
+    }
+    harness.tap(keymap::cursor_left());
+    harness.tap(keymap::menu_down());
+    harness.tap(keymap::confirm());
+    harness.step();
+    let settings = harness.app.world.resource::<Settings>();
+    assert_eq!(settings.theme, manifest().themes.len() - 1);
+}
+
+#[test]
+fn hud_and_menus_use_the_theme_ink() {
+    let themes = manifest().themes;
+    let index = themes
+        .iter()
+        .position(|theme| theme.name == "night gym")
+        .unwrap();
+    let mut harness = loaded(Settings {
+        title_screen: true,
+        ..Settings::default()
+    });
+    harness.app.world.resource_mut::<Settings>().theme = index;
+    harness.steps(2);
+    let ink = themes[index].ink;
+    assert_ne!(ink, Theme::default().ink);
+
+    let world = &mut harness.app.world;
+    let texts: Vec<Color> = world
+        .query_filtered::<&BitmapText, Or<(With<TitleHeading>, With<TitleBody>)>>()
+        .iter(world)
+        .map(|text| text.sections[0].color)
+        .collect();
+    assert_eq!(texts.len(), 2);
+    assert!(texts.iter().all(|color| *color == ink));
+    // the entries not selected are faded, but in the same ink
+    let items: Vec<Color> = world
+        .query_filtered::<&BitmapText, With<TitleItem>>()
+        .iter(world)
+        .map(|text| text.sections[0].color)
+        .collect();
+    assert!(items.contains(&ink));
+    assert!(items.iter().all(|color| color.with_a(1.0) == ink));
+    let score = world
+        .query_filtered::<&BitmapText, With<ScoreUI>>()
+        .single(world);
+    assert_eq!(score.sections[0].color, ink);
+    let message = world
+        .query_filtered::<&BitmapText, With<bordtennis::state::State>>()
+        .single(world);
+    assert_eq!(message.sections[0].value, "NEW GAME");
+    assert_eq!(message.sections[0].color, ink);
+}
+
+#[test]
+fn music_stems_follow_the_theme() {
+    let themes = manifest().themes;
+    let index = themes
+        .iter()
+        .position(|theme| theme.music.is_some())
+        .unwrap();
+    let mut harness = loaded(Settings::default());
+    assert_eq!(
+        stems(&mut harness),
+        ["music-step-0.ogg", "music-step-1.ogg", "music-step-2.ogg"]
+    );
+
+    harness.app.world.resource_mut::<Settings>().theme = index;
+    harness.steps(2);
+    let music = themes[index].music.clone().unwrap();
+    assert_eq!(stems(&mut harness), music.stems);
+    assert_eq!(harness.app.world.resource::<MusicMix>().bpm, music.bpm);
+
+    harness.app.world.resource_mut::<Settings>().theme = 0;
+    harness.steps(2);
+    assert_eq!(stems(&mut harness)[0], "music-step-0.ogg");
+    assert_eq!(stems(&mut harness).len(), 3);
+}
@@ -0,0 +1,363 @@
+mod common;
+
+use bevy::{prelude::*, window::ReceivedCharacter};
+use bordtennis::{
+    bat::{BatInput, Controller, Variant},
+    headless, keymap,
+    players::PlayerSettings,
+    replay::{Playback, Replay},
+    settings::{GameMode, MatchFormat, Settings},
+    state::GameState,
+    text::BitmapText,
+    title::{Screen, SetupRow, TitleAction, TitleItem},
+    tournament::{Tournament, TournamentUI},
+    tuning::GameTuning,
+};
+use common::Harness;
+
+fn title_screen() -> Harness {
+    let mut harness = Harness::with_settings(Settings {
+        title_screen: true,
+        ..Settings::default()
+    });
+    harness.step();
+    harness
+}
+
+fn screen(harness: &Harness) -> &Screen {
+    harness.app.world.resource::<Screen>()
+}
+
+fn pick(harness: &mut Harness, action: TitleAction) {
+    let index = TitleAction::ALL
+        .iter()
+        .position(|other| *other == action)
+        .unwrap();
+    for _ in 0..index {
+        harness.tap(keymap::menu_down());
+    }
+    harness.tap(keymap::confirm());
+}
+
+/// Moves down the setup screen to `row`.
+fn to_row(harness: &mut Harness, row: SetupRow) {
+    let Screen::Setup(setup) = screen(harness) else {
+        panic!("not on the setup screen");
+    };
+    let index = setup.rows().iter().position(|other| *other == row).unwrap();
+    for _ in setup.row..index {
+        harness.tap(keymap::menu_down());
+    }
+}
+
+fn controller(harness: &mut Harness, variant: Variant) -> Option<&'static str> {
+    let world = &mut harness.app.world;
+    world
+        .query::<(&bordtennis::bat::Bat, &Controller)>()
+        .iter(world)
+        .find(|(bat, _)| bat.variant == variant)
+        .map(|(_, controller)| match controller {
+            Controller::Keyboard => "keyboard",
+            Controller::Cpu(_) => "cpu",
+            Controller::Replay => "replay",
+        })
+}
+
+#[test]
+fn starts_on_the_title_screen() {
+    let mut harness = title_screen();
+    assert_eq!(screen(&harness), &Screen::Title { selected: 0 });
+    harness.tap(keymap::pause());
+    harness.step();
+    assert_eq!(harness.game_state(), &GameState::NewGame);
+}
+
+#[test]
+fn no_title_screen_without_the_setting() {
+    let harness = Harness::new();
+    assert!(!harness.app.world.contains_resource::<Screen>());
+}
+
+#[test]
+fn cpu_match_is_set_up_before_the_serve() {
+    let mut harness = title_screen();
+    pick(&mut harness, TitleAction::Cpu);
+    assert!(matches!(screen(&harness), Screen::Setup(_)));
+    assert_eq!(harness.game_state(), &GameState::NewGame);
+
+    // difficulty, then points from 1 to 11, win by 2, best of 3, the arena as it is
+    to_row(&mut harness, SetupRow::Difficulty);
+    harness.tap(keymap::cursor_right());
+    harness.tap(keymap::menu_down());
+    for _ in 0..3 {
+        harness.tap(keymap::cursor_right());
+    }
+    harness.tap(keymap::menu_down());
+    harness.tap(keymap::cursor_right());
+    harness.tap(keymap::menu_down());
+    harness.tap(keymap::cursor_right());
+    to_row(&mut harness, SetupRow::Start);
+    harness.tap(keymap::confirm());
+    harness.step();
+
+    assert_eq!(harness.game_state(), &GameState::Playing);
+    assert!(!harness.app.world.contains_resource::<Screen>());
+    let settings = harness.app.world.resource::<Settings>();
+    assert_eq!(settings.mode, GameMode::Cpu);
+    assert_eq!(
+        settings.format,
+        MatchFormat {
+            points_per_game: 11,
+            win_by: 2,
+            best_of: 3,
+        }
+    );
+    assert_eq!(controller(&mut harness, Variant::Dark), Some("cpu"));
+    assert_eq!(controller(&mut harness, Variant::Light), Some("keyboard"));
+}
+
+#[test]
+fn names_and_colors_are_picked_before_the_serve() {
+    let mut harness = title_screen();
+    pick(&mut harness, TitleAction::Versus);
+    for char in "ada".chars() {
+        harness.app.world.send_event(ReceivedCharacter {
+            window: Entity::PLACEHOLDER,
+            char,
+        });
+        harness.step();
+    }
+    to_row(&mut harness, SetupRow::DarkBat);
+    harness.tap(keymap::cursor_right());
+    to_row(&mut harness, SetupRow::Start);
+    harness.tap(keymap::confirm());
+    harness.step();
+
+    assert_eq!(harness.game_state(), &GameState::Playing);
+    let players = harness.app.world.resource::<PlayerSettings>();
+    assert_eq!(players.light.name, "ADA");
+    assert_eq!(players.light.palette, 0);
+    assert_eq!(players.dark.palette, 1);
+}
+
+fn type_text(harness: &mut Harness, text: &str) {
+    for char in text.chars() {
+        harness.app.world.send_event(ReceivedCharacter {
+            window: Entity::PLACEHOLDER,
+            char,
+        });
+        harness.step();
+    }
+}
+
+/// Plays until the match is won, skipping the instant replay, and says who won.
+fn finish_match(harness: &mut Harness) -> Variant {
+    while !harness.state().match_over {
+        harness.step();
+    }
+    harness.tap(keymap::pause());
+    harness.step();
+    match harness.game_state() {
+        GameState::Winner(winner) => winner.clone(),
+        other => panic!("no winner but {other:?}"),
+    }
+}
+
+fn names(harness: &Harness) -> (String, String) {
+    let players = harness.app.world.resource::<PlayerSettings>();
+    (players.light.name.clone(), players.dark.name.clone())
+}
+
+#[test]
+fn tournament_plays_a_bracket_of_named_players() {
+    let mut harness = title_screen();
+    pick(&mut harness, TitleAction::Tournament);
+    type_text(&mut harness, "ann");
+    harness.tap(keymap::confirm());
+    type_text(&mut harness, "bob");
+    // the third keeps the default name
+    harness.tap(keymap::menu_down());
+    harness.tap(keymap::menu_down());
+    type_text(&mut harness, "dee");
+    harness.tap(keymap::confirm());
+    harness.tap(keymap::confirm());
+    let Screen::Setup(setup) = screen(&harness) else {
+        panic!("not on the setup screen");
+    };
+    assert_eq!(
+        setup.entrants,
+        Some(["ANN".into(), "BOB".into(), String::new(), "DEE".into()])
+    );
+    assert!(!setup.rows().contains(&SetupRow::LightName));
+    to_row(&mut harness, SetupRow::Start);
+    harness.tap(keymap::confirm());
+    harness.step();
+    assert_eq!(names(&harness), ("ANN".into(), "BOB".into()));
+
+    let pick_winner = |winner: Variant, light: &str, dark: &str| match winner {
+        Variant::Light => light.to_string(),
+        Variant::Dark => dark.to_string(),
+    };
+    let first = pick_winner(finish_match(&mut harness), "ANN", "BOB");
+    let world = &mut harness.app.world;
+    let bracket = world
+        .query_filtered::<&BitmapText, With<TournamentUI>>()
+        .single(world);
+    assert!(bracket.sections[0].value.contains(&format!("> {first}")));
+
+    harness.tap(keymap::pause());
+    harness.step();
+    assert_eq!(names(&harness), ("P3".into(), "DEE".into()));
+    let second = pick_winner(finish_match(&mut harness), "P3", "DEE");
+
+    harness.tap(keymap::pause());
+    harness.step();
+    assert_eq!(names(&harness), (first.clone(), second.clone()));
+    let champion = pick_winner(finish_match(&mut harness), &first, &second);
+    let tournament = harness.app.world.resource::<Tournament>();
+    assert_eq!(tournament.champion(), Some(champion));
+
+    // back to the title with the names from before
+    harness.tap(keymap::pause());
+    harness.steps(2);
+    assert_eq!(screen(&harness), &Screen::Title { selected: 0 });
+    assert!(!harness.app.world.contains_resource::<Tournament>());
+    assert_eq!(names(&harness), (String::new(), String::new()));
+}
+
+#[test]
+fn practice_has_no_second_player() {
+    let mut harness = title_screen();
+    pick(&mut harness, TitleAction::Practice);
+    to_row(&mut harness, SetupRow::Start);
+    harness.tap(keymap::confirm());
+    harness.step();
+    assert_eq!(harness.game_state(), &GameState::Playing);
+    assert!(controller(&mut harness, Variant::Dark).is_none());
+    harness.steps(10);
+    assert_eq!(harness.game_state(), &GameState::Playing);
+}
+
+#[test]
+fn escape_goes_back_to_the_entry_picked() {
+    let mut harness = title_screen();
+    pick(&mut harness, TitleAction::Credits);
+    assert_eq!(screen(&harness), &Screen::Credits);
+    harness.tap(keymap::menu_close());
+    assert_eq!(
+        screen(&harness),
+        &Screen::Title {
+            selected: TitleAction::Credits as usize
+        }
+    );
+}
+
+/// Puts the mouse over the title entry for `action`.
+fn point_at(harness: &mut Harness, action: TitleAction) {
+    let world = &mut harness.app.world;
+    let (_, text, transform) = world
+        .query::<(&TitleItem, &BitmapText, &GlobalTransform)>()
+        .iter(world)
+        .find(|(_, text, _)| text.sections[0].value == action.label())
+        .unwrap();
+    // halfway down the entry, which hangs from its top
+    let line = text.size().y as f32 * transform.compute_transform().scale.y;
+    let point = transform.translation().truncate() - Vec2::new(0.0, 0.5 * line);
+    let mut window = world.query::<&mut Window>().single_mut(world);
+    let center = Vec2::new(window.width(), window.height()) * 0.5;
+    window.set_cursor_position(Some(center + Vec2::new(point.x, -point.y)));
+}
+
+#[test]
+fn entries_are_picked_with_the_mouse() {
+    let mut app = headless::app(Settings {
+        title_screen: true,
+        ..Settings::default()
+    });
+    // the menu entries are found where they're drawn
+    app.add_plugins(TransformPlugin)
+        .init_resource::<Input<MouseButton>>();
+    app.update();
+    let mut harness = Harness { app };
+    harness.step();
+
+    point_at(&mut harness, TitleAction::Credits);
+    harness.step();
+    assert_eq!(
+        screen(&harness),
+        &Screen::Title {
+            selected: TitleAction::Credits as usize
+        }
+    );
+    // moving the keys away isn't undone while the mouse stays put
+    harness.tap(keymap::menu_up());
+    harness.step();
+    assert_eq!(
+        screen(&harness),
+        &Screen::Title {
+            selected: TitleAction::Credits as usize - 1
+        }
+    );
+
+    point_at(&mut harness, TitleAction::Practice);
+    let mut mouse = harness.app.world.resource_mut::<Input<MouseButton>>();
+    mouse.press(MouseButton::Left);
+    harness.step();
+    let Screen::Setup(setup) = screen(&harness) else {
+        panic!("the press should open the match setup");
+    };
+    assert_eq!(setup.mode, GameMode::Practice);
+}
+
+#[test]
+fn quitting_a_match_returns_to_the_title() {
+    let mut harness = title_screen();
+    pick(&mut harness, TitleAction::Versus);
+    to_row(&mut harness, SetupRow::Start);
+    harness.tap(keymap::confirm());
+    harness.steps(5);
+    harness.tap(keymap::restart());
+    harness.step();
+    assert_eq!(screen(&harness), &Screen::Title { selected: 0 });
+}
+
+#[test]
+fn replays_are_played_from_the_menu() {
+    let dir = std::env::temp_dir().join(format!("bordtennis-title-{}", std::process::id()));
+    let mut replay = Replay::new(&Settings::default(), &GameTuning::default(), 7);
+    for _ in 0..30 {
+        replay.push(
+            &BatInput {
+                left: true,
+                ..default()
+            },
+            &BatInput::default(),
+        );
+    }
+    replay.save(&dir.join("replay-1-0.ron")).unwrap();
+
+    let mut harness = Harness::with_settings(Settings {
+        title_screen: true,
+        replay_dir: Some(dir.clone()),
+        ..Settings::default()
+    });
+    harness.step();
+    pick(&mut harness, TitleAction::Replays);
+    let files = match screen(&harness) {
+        Screen::Replays { files, .. } => files.clone(),
+        other => panic!("expected the replays page, got {other:?}"),
+    };
+    assert_eq!(files, vec![dir.join("replay-1-0.ron")]);
+
+    harness.tap(keymap::confirm());
+    std::fs::remove_dir_all(&dir).unwrap();
+    harness.steps(5);
+    assert!(harness.app.world.contains_resource::<Playback>());
+    assert!(!harness.app.world.contains_resource::<Screen>());
+    assert_eq!(controller(&mut harness, Variant::Light), Some("replay"));
+
+    harness.tap(keymap::menu_close());
+    harness.step();
+    assert!(!harness.app.world.contains_resource::<Playback>());
+    assert_eq!(screen(&harness), &Screen::Title { selected: 0 });
+}
@@ -0,0 +1,39 @@
+#![cfg(not(feature = "singleplayer"))]
+
+mod common;
+
+use bevy::prelude::*;
+use bordtennis::{
+    ball::Ball,
+    keymap,
+    tuning::{GameTuning, TuningHandle, TUNING_PATH},
+};
+use common::Harness;
+
+#[test]
+fn tuning_asset_matches_defaults() {
+    let path = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), TUNING_PATH);
+    let source = std::fs::read_to_string(path).expect("tuning asset should exist");
+    let tuning: GameTuning = ron::from_str(&source).expect("tuning asset should parse");
+    assert_eq!(tuning, GameTuning::default());
+}
+
+#[test]
+fn serve_speed_is_read_from_tuning() {
+    let mut harness = Harness::new();
+    for _ in 0..600 {
+        let world = &harness.app.world;
+        let handle = &world.resource::<TuningHandle>().0;
+        if world.resource::<Assets<GameTuning>>().contains(handle) {
+            break;
+        }
+        harness.step();
+    }
+    harness.steps(2);
+    harness.app.world.resource_mut::<GameTuning>().serve_speed = 1.0;
+
+    harness.tap(keymap::pause());
+    harness.tap(keymap::restart());
+    let ball = harness.ball();
+    assert_eq!(ball.velocity.y, Ball::default().velocity.y * 2.0);
+}